@@ -0,0 +1,44 @@
+use crate::usage;
+use lzs::{Complzss, ContainerWriteError, VecWriter};
+use std::io::{stdin, stdout, Read, Write};
+
+// Subcommand for the `comp`/`lzss` kernelcache container, reads stdin and writes stdout.
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let encode = match args {
+        [command] if command == "e" => true,
+        [command] if command == "d" => false,
+        _ => usage("unknown complzss command, use 'e' or 'd'"),
+    };
+    let mut input = Vec::new();
+    stdin()
+        .read_to_end(&mut input)
+        .map_err(|err| format!("error while reading: {err}"))?;
+    let output = if encode {
+        match Complzss::compress(&input, VecWriter::with_capacity(input.len())) {
+            Ok(output) => output,
+            Err(ContainerWriteError::Container(err)) => {
+                return Err(format!("can't compress: {err}"))
+            }
+            Err(ContainerWriteError::Write(err)) => match err {},
+        }
+    } else {
+        let container = Complzss::parse(&input).map_err(|err| format!("invalid header: {err}"))?;
+        if !container.platform_name().is_empty() {
+            eprintln!(
+                "platform name: {}",
+                String::from_utf8_lossy(container.platform_name())
+            );
+        }
+        if !container.trailing().is_empty() {
+            eprintln!("ignoring {} trailing bytes", container.trailing().len());
+        }
+        container
+            .decompress()
+            .map_err(|err| format!("invalid data: {err}"))?
+    };
+    stdout()
+        .write_all(&output)
+        .and_then(|()| stdout().flush())
+        .map_err(|err| format!("error while writing: {err}"))
+}
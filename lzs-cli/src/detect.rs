@@ -0,0 +1,59 @@
+use crate::usage;
+use lzs::{Detection, Dialect, LengthHeader};
+
+// Subcommand ranking the stream layouts which may fit an unknown file.
+
+/// The number of layouts printed without `--all`.
+const TOP: usize = 5;
+
+pub fn header(header: LengthHeader) -> &'static str {
+    match header {
+        LengthHeader::None => "none",
+        LengthHeader::Compressed => "compressed",
+        LengthHeader::Decompressed => "decompressed",
+    }
+}
+
+fn line(detection: &Detection) -> String {
+    let dialect = detection.dialect;
+    let c = detection
+        .c
+        .map_or_else(|| "-".to_string(), |c| format!("0x{c:02x}"));
+    format!(
+        "{:5}  {:2} {:2}  {:7}  {:12}  {:4}  {:12}  {:7} {:10} {:8} {:8}",
+        detection.score,
+        dialect.position_bits(),
+        dialect.length_bits(),
+        u8::from(dialect.literal_flag()),
+        header(dialect.header()),
+        c,
+        detection.decompressed,
+        detection.invalid,
+        detection.extendable,
+        detection.repeated,
+        detection.trailing,
+    )
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let (all, path) = match args {
+        [path] if !path.starts_with("--") => (false, path),
+        [option, path] if option == "--all" => (true, path),
+        _ => usage("expected [--all] <file>"),
+    };
+    let input = std::fs::read(path).map_err(|err| format!("can't read {path}: {err}"))?;
+    let detections = Dialect::detect(&input);
+
+    println!("score  EI EJ  literal  header        c     decompressed  invalid extendable repeated trailing");
+    for detection in detections.iter().take(if all { usize::MAX } else { TOP }) {
+        println!("{}", line(detection));
+    }
+    match detections.first() {
+        Some(best) if best.score > 0 && best.dialect == Dialect::LZS => println!(
+            "decompress the most likely layout with: lzs d 0x{:02x}",
+            best.c.unwrap_or(0)
+        ),
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+use crate::{parse_dec_or_hex_u8, usage};
+use lzs::{Lzs, SliceReader, Token, Tokens};
+use std::fmt::Write as _;
+use std::io::{stdout, BufWriter, Write};
+
+// Subcommand printing the flag bytes and tokens of a compressed file.
+
+struct Options {
+    hex: bool,
+    ascii: bool,
+    json: bool,
+    c: u8,
+    path: String,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        hex: false,
+        ascii: false,
+        json: false,
+        c: 0x20,
+        path: String::new(),
+    };
+    let mut args = args.iter();
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--hex" => options.hex = true,
+            "--ascii" => options.ascii = true,
+            "--json" => options.json = true,
+            "--c" => {
+                let c = args.next().ok_or("missing value of --c")?;
+                options.c = parse_dec_or_hex_u8(c.trim()).map_err(|_| "can't read c")?;
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err("more than one file".to_string()),
+        }
+    }
+    options.path = path.ok_or("missing file")?;
+    Ok(options)
+}
+
+/// A hex column.
+fn hex(bytes: &[u8]) -> String {
+    let mut column = String::with_capacity(bytes.len() * 3);
+    for (i, c) in bytes.iter().enumerate() {
+        if i > 0 {
+            column.push(' ');
+        }
+        write!(column, "{c:02x}").unwrap();
+    }
+    column
+}
+
+/// An ASCII column, other bytes are shown as dots.
+fn ascii(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|c| {
+            if c.is_ascii_graphic() || *c == b' ' {
+                *c as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+fn columns(options: &Options, bytes: &[u8]) -> String {
+    let mut columns = Vec::new();
+    if options.hex {
+        columns.push(hex(bytes));
+    }
+    if options.ascii {
+        columns.push(format!("|{}|", ascii(bytes)));
+    }
+    if columns.is_empty() {
+        columns.push(format!("\"{}\"", bytes.escape_ascii()));
+    }
+    columns.join("  ")
+}
+
+fn json(kind: &str, offset: usize, fields: &str) -> String {
+    format!("{{\"type\": \"{kind}\", \"offset\": {offset}{fields}}}")
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = parse_options(args).unwrap_or_else(|err| usage(&err));
+    let input = std::fs::read(&options.path)
+        .map_err(|err| format!("can't read {}: {err}", options.path))?;
+    let output = Lzs::new(options.c).decompress_vec(&input);
+
+    let mut out = BufWriter::new(stdout());
+    dump(&options, &input, &output, &mut out)
+        .and_then(|()| out.flush())
+        .map_err(|err| format!("error while writing: {err}"))
+}
+
+fn dump<W: Write>(
+    options: &Options,
+    input: &[u8],
+    output: &[u8],
+    out: &mut W,
+) -> std::io::Result<()> {
+    // JSON objects are separated by commas, text lines follow a header.
+    let mut first = true;
+    let mut line = |out: &mut W, line: String| {
+        if !options.json {
+            writeln!(out, "{line}")
+        } else if first {
+            first = false;
+            write!(out, "[\n  {line}")
+        } else {
+            write!(out, ",\n  {line}")
+        }
+    };
+    if !options.json {
+        writeln!(out, "offset   output   token   pos len  bytes")?;
+    }
+
+    let mut end = 0;
+    let mut decompressed = 0;
+    for (offset, token) in Tokens::new(SliceReader::new(input)) {
+        if offset > end {
+            // The flag byte before the next eight tokens, the lowest bit first.
+            let flags = input[end];
+            line(
+                out,
+                if options.json {
+                    json("flags", end, &format!(", \"value\": {flags}"))
+                } else {
+                    format!("{end:08x}          flags   {:08b}", flags.reverse_bits())
+                },
+            )?;
+        }
+        let bytes = &output[decompressed..decompressed + token.decompressed_len()];
+        line(
+            out,
+            match (token, options.json) {
+                (Token::Literal(c), true) => json(
+                    "literal",
+                    offset,
+                    &format!(", \"output\": {decompressed}, \"value\": {c}"),
+                ),
+                (Token::Match { pos, len }, true) => json(
+                    "match",
+                    offset,
+                    &format!(
+                        ", \"output\": {decompressed}, \"pos\": {pos}, \"len\": {len}, \"bytes\": \"{}\"",
+                        hex(bytes).replace(' ', "")
+                    ),
+                ),
+                (Token::Literal(_), false) => format!(
+                    "{offset:08x} {decompressed:08x} literal          {}",
+                    columns(options, bytes)
+                ),
+                (Token::Match { pos, len }, false) => format!(
+                    "{offset:08x} {decompressed:08x} match   {pos:03x} {len:2}   {}",
+                    columns(options, bytes)
+                ),
+            },
+        )?;
+        end = offset + token.compressed_len();
+        decompressed += token.decompressed_len();
+    }
+    if end < input.len() {
+        let trailing = input.len() - end;
+        line(
+            out,
+            if options.json {
+                json("incomplete", end, &format!(", \"len\": {trailing}"))
+            } else {
+                format!("{end:08x}          incomplete unit of {trailing} bytes")
+            },
+        )?;
+    }
+    if !options.json {
+        Ok(())
+    } else if first {
+        writeln!(out, "[]")
+    } else {
+        writeln!(out, "\n]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dump, Options};
+
+    /// Two literals and a match repeating them: "abababab".
+    const STREAM: &[u8] = &[0b011, b'a', b'b', 0xee, 0xf3];
+
+    fn options(json: bool) -> Options {
+        Options {
+            hex: true,
+            ascii: true,
+            json,
+            c: 0x20,
+            path: String::new(),
+        }
+    }
+
+    fn dump_string(options: &Options, input: &[u8]) -> String {
+        let output = lzs::Lzs::new(options.c).decompress_vec(input);
+        let mut out = Vec::new();
+        dump(options, input, &output, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Skip a JSON value of the dump (objects, arrays, strings and unsigned numbers).
+    fn skip_value(json: &[u8], mut i: usize) -> Option<usize> {
+        let skip_ws = |mut i: usize| {
+            while json.get(i).is_some_and(u8::is_ascii_whitespace) {
+                i += 1;
+            }
+            i
+        };
+        i = skip_ws(i);
+        match *json.get(i)? {
+            b'"' => {
+                i += 1;
+                while *json.get(i)? != b'"' {
+                    i += if json[i] == b'\\' { 2 } else { 1 };
+                }
+                Some(i + 1)
+            }
+            open @ (b'[' | b'{') => {
+                let close = if open == b'[' { b']' } else { b'}' };
+                i = skip_ws(i + 1);
+                if *json.get(i)? == close {
+                    return Some(i + 1);
+                }
+                loop {
+                    if open == b'{' {
+                        i = skip_ws(skip_value(json, i)?);
+                        (*json.get(i)? == b':').then_some(())?;
+                        i += 1;
+                    }
+                    i = skip_ws(skip_value(json, i)?);
+                    match *json.get(i)? {
+                        b',' => i += 1,
+                        c if c == close => return Some(i + 1),
+                        _ => return None,
+                    }
+                }
+            }
+            b'0'..=b'9' => {
+                while json.get(i).is_some_and(u8::is_ascii_digit) {
+                    i += 1;
+                }
+                Some(i)
+            }
+            _ => None,
+        }
+    }
+
+    fn is_json(json: &str) -> bool {
+        skip_value(json.as_bytes(), 0) == Some(json.trim_end().len())
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            dump_string(&options(false), STREAM),
+            "offset   output   token   pos len  bytes\n\
+             00000000          flags   11000000\n\
+             00000001 00000000 literal          61  |a|\n\
+             00000002 00000001 literal          62  |b|\n\
+             00000003 00000002 match   fee  6   61 62 61 62 61 62  |ababab|\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let json = dump_string(&options(true), STREAM);
+        assert!(is_json(&json));
+        assert!(json.contains(r#"{"type": "match", "offset": 3, "output": 2, "pos": 4078, "len": 6, "bytes": "616261626162"}"#));
+
+        assert_eq!(dump_string(&options(true), &[]), "[]\n");
+        // a match without its second byte
+        let json = dump_string(&options(true), &STREAM[..4]);
+        assert!(is_json(&json));
+        assert!(json.contains(r#"{"type": "incomplete", "offset": 3, "len": 1}"#));
+        // a flag byte without units
+        let json = dump_string(&options(true), &STREAM[..1]);
+        assert!(is_json(&json));
+        assert!(json.contains(r#"{"type": "incomplete", "offset": 0, "len": 1}"#));
+        assert!(!is_json(&json[..json.len() - 2]));
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{parse_dec_or_hex_u8, usage};
+use lzs::{Ff8Archive, Ff8ArchiveWriter, Ff8Compression, Ff8Entry, Lzs};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+// Subcommands for Final Fantasy VIII archives (`.fi`, `.fl` and `.fs`).
+
+struct Files {
+    index: Vec<u8>,
+    file_list: Vec<u8>,
+    data: Vec<u8>,
+}
+
+fn archive_path(archive: &str, extension: &str) -> PathBuf {
+    let path = Path::new(archive);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("fi" | "fl" | "fs") => path.with_extension(extension),
+        _ => PathBuf::from(format!("{archive}.{extension}")),
+    }
+}
+
+fn read_files(archive: &str) -> Result<Files, String> {
+    let read = |extension| {
+        let path = archive_path(archive, extension);
+        fs::read(&path).map_err(|err| format!("can't read {}: {err}", path.display()))
+    };
+    Ok(Files {
+        index: read("fi")?,
+        file_list: read("fl")?,
+        data: read("fs")?,
+    })
+}
+
+fn write_files(
+    archive: &str,
+    (index, file_list, data): (Vec<u8>, Vec<u8>, Vec<u8>),
+) -> Result<(), String> {
+    for (extension, content) in [("fi", index), ("fl", file_list), ("fs", data)] {
+        let path = archive_path(archive, extension);
+        fs::write(&path, content)
+            .map_err(|err| format!("can't write {}: {err}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn open(files: &Files) -> Result<Ff8Archive<'_>, String> {
+    Ff8Archive::open(&files.index, &files.file_list, &files.data)
+        .map_err(|err| format!("can't open archive: {err}"))
+}
+
+fn parse_lzs(c: &str) -> Result<Lzs, String> {
+    Ok(Lzs::new(
+        parse_dec_or_hex_u8(c.trim()).map_err(|_| "can't read c")?,
+    ))
+}
+
+/// The path of an entry below `dir`, without the drive (`c:\ff8\data\x` becomes `dir/ff8/data/x`).
+///
+/// Names which would leave `dir`, with `..`, root or prefix parts, are an error.
+fn local_path(dir: &str, entry: &Ff8Entry<'_>) -> Result<PathBuf, String> {
+    let name = entry.name();
+    let name = name.find(":\\").map_or(name, |pos| &name[pos + 2..]);
+    let mut path = PathBuf::from(dir);
+    for part in name.split('\\') {
+        for component in Path::new(part).components() {
+            match component {
+                Component::Normal(part) => path.push(part),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                    return Err(format!("entry {} leaves the directory", entry.name()));
+                }
+            }
+        }
+    }
+    Ok(path)
+}
+
+fn compression_name(compression: Ff8Compression) -> &'static str {
+    match compression {
+        Ff8Compression::None => "none",
+        Ff8Compression::Lzs => "lzs",
+        Ff8Compression::Lz4 => "lz4",
+    }
+}
+
+fn list(archive: &str) -> Result<(), String> {
+    let files = read_files(archive)?;
+    let archive = open(&files)?;
+    for entry in archive.entries() {
+        let stored = archive
+            .raw(entry)
+            .map_or_else(|_| "?".to_string(), |raw| raw.len().to_string());
+        println!(
+            "{:>10} {:>10} {:<4} {}",
+            entry.size(),
+            stored,
+            compression_name(entry.compression()),
+            entry.name()
+        );
+    }
+    Ok(())
+}
+
+fn extract(c: &str, archive: &str, dir: &str, name: Option<&str>) -> Result<(), String> {
+    let lzs = parse_lzs(c)?;
+    let files = read_files(archive)?;
+    let archive = open(&files)?;
+    let entries: Vec<_> = match name {
+        Some(name) => {
+            let suffix = format!("\\{name}").to_ascii_lowercase();
+            let entry = archive
+                .find(name)
+                .or_else(|| {
+                    archive
+                        .entries()
+                        .iter()
+                        .find(|e| e.name().to_ascii_lowercase().ends_with(&suffix))
+                })
+                .ok_or_else(|| format!("entry {name} not found"))?;
+            vec![*entry]
+        }
+        None => archive.entries().to_vec(),
+    };
+    let mut failed = 0;
+    for entry in &entries {
+        let result = local_path(dir, entry).and_then(|path| {
+            let content = archive.extract(entry, lzs).map_err(|err| err.to_string())?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+            }
+            fs::write(&path, content).map_err(|err| err.to_string())
+        });
+        if let Err(err) = result {
+            eprintln!("can't extract {}: {err}", entry.name());
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{failed} of {} entries failed", entries.len()));
+    }
+    Ok(())
+}
+
+fn rebuild(c: &str, archive: &str, new_archive: &str, dir: Option<&str>) -> Result<(), String> {
+    let lzs = parse_lzs(c)?;
+    let files = read_files(archive)?;
+    let archive = open(&files)?;
+    let mut writer = Ff8ArchiveWriter::new(lzs);
+    for entry in archive.entries() {
+        let replacement = dir
+            .map(|dir| local_path(dir, entry))
+            .transpose()
+            .map_err(|err| format!("can't rebuild {}: {err}", entry.name()))?
+            .filter(|p| p.is_file());
+        let result = if let Some(path) = replacement {
+            // LZ4 can't be compressed, thus replaced entries are compressed with LZS
+            let compression = match entry.compression() {
+                Ff8Compression::Lz4 => Ff8Compression::Lzs,
+                compression => compression,
+            };
+            fs::read(&path)
+                .map_err(|err| format!("can't read {}: {err}", path.display()))
+                .and_then(|content| {
+                    writer
+                        .add(entry.name(), &content, compression)
+                        .map_err(|err| err.to_string())
+                })
+        } else if entry.compression() == Ff8Compression::Lzs {
+            archive
+                .extract(entry, lzs)
+                .and_then(|content| writer.add(entry.name(), &content, Ff8Compression::Lzs))
+                .map_err(|err| err.to_string())
+        } else {
+            archive
+                .raw(entry)
+                .and_then(|raw| writer.add_raw(entry, raw))
+                .map_err(|err| err.to_string())
+        };
+        result.map_err(|err| format!("can't rebuild {}: {err}", entry.name()))?;
+    }
+    write_files(new_archive, writer.finish())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let args: Vec<_> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["list", archive] => list(archive),
+        ["extract", c, archive, dir] => extract(c, archive, dir, None),
+        ["extract", c, archive, dir, name] => extract(c, archive, dir, Some(name)),
+        ["rebuild", c, archive, new_archive] => rebuild(c, archive, new_archive, None),
+        ["rebuild", c, archive, new_archive, dir] => rebuild(c, archive, new_archive, Some(dir)),
+        _ => usage("unknown ff8 command or wrong number of arguments"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::local_path;
+    use lzs::{Ff8Archive, Ff8ArchiveWriter, Ff8Compression, Lzs};
+    use std::path::Path;
+
+    #[test]
+    fn test_local_path() {
+        let names = [
+            "c:\\ff8\\data\\x.dat",
+            "c:\\ff8\\..\\..\\escape",
+            "c:\\ff8\\/etc/passwd",
+            "c:\\ff8\\a/../../escape",
+            "\\ff8\\.\\y.dat",
+        ];
+        let mut writer = Ff8ArchiveWriter::new(Lzs::new(0));
+        for name in names {
+            writer.add(name, b"data", Ff8Compression::None).unwrap();
+        }
+        let (index, file_list, data) = writer.finish();
+        let archive = Ff8Archive::open(&index, &file_list, &data).unwrap();
+        let paths: Vec<_> = archive
+            .entries()
+            .iter()
+            .map(|entry| local_path("out", entry))
+            .collect();
+        assert_eq!(paths[0].as_deref(), Ok(Path::new("out/ff8/data/x.dat")));
+        for path in &paths[1..4] {
+            assert!(path.is_err());
+        }
+        assert_eq!(paths[4].as_deref(), Ok(Path::new("out/ff8/y.dat")));
+    }
+}
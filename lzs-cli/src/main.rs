@@ -7,12 +7,19 @@ use std::process::exit;
 use std::rc::Rc;
 use std::str::FromStr;
 
+mod complzss;
+mod detect;
+mod dump;
+mod ff8;
+mod scan;
+
 // This is a very rudimentary program, everybody is welcome to improve it.
 
 #[derive(Debug)]
 struct Args {
     encode: bool,
     c: u8,
+    level: u8,
 }
 
 fn parse_dec_or_hex_u8(i: &str) -> Result<u8, ParseIntError> {
@@ -24,7 +31,15 @@ fn parse_dec_or_hex_u8(i: &str) -> Result<u8, ParseIntError> {
 }
 
 fn parse_args() -> Result<Args, &'static str> {
-    let args: Vec<_> = std::env::args().collect();
+    let mut args: Vec<_> = std::env::args().collect();
+    let mut level = Lzs::DEFAULT_LEVEL;
+    if let Some(arg) = args.get(1).and_then(|arg| arg.strip_prefix('-')) {
+        level = match arg.as_bytes() {
+            [digit @ b'0'..=b'9'] => digit - b'0',
+            _ => return Err("unknown option, use -0 .. -9 for the compression level"),
+        };
+        args.remove(1);
+    }
     if args.len() != 3 {
         return Err("not exactly 2 arguments");
     }
@@ -39,7 +54,7 @@ fn parse_args() -> Result<Args, &'static str> {
     }
     let c = parse_dec_or_hex_u8(params[0].trim()).map_err(|_| "can't read c")?;
 
-    Ok(Args { encode, c })
+    Ok(Args { encode, c, level })
 }
 
 struct ReadCounter<T>(T, Rc<RefCell<usize>>);
@@ -77,15 +92,40 @@ impl<T: Write> Write for WriteCounter<T> {
     }
 }
 
+fn usage(err: &str) -> ! {
+    let name = std::env::args().next().unwrap();
+    eprintln!("error: {err}");
+    eprintln!("usage: {name} [-0 .. -9] <'e'|'d'> <c>");
+    eprintln!("       {name} ff8 list <archive>");
+    eprintln!("       {name} ff8 extract <c> <archive> <dir> [name]");
+    eprintln!("       {name} ff8 rebuild <c> <archive> <new-archive> [dir]");
+    eprintln!("       {name} complzss <'e'|'d'>");
+    eprintln!("       {name} dump [--hex] [--ascii] [--json] [--c <c>] <file>");
+    eprintln!("       {name} detect [--all] <file>");
+    eprintln!("       {name} scan [--min-score <score>] [--extract <dir>] <file>");
+    eprintln!("example: {name} e 0x20");
+    exit(1)
+}
+
 fn main() {
-    let args = parse_args().unwrap_or_else(|err| {
-        let name = std::env::args().next().unwrap();
-        eprintln!("error: {err}");
-        eprintln!("usage: {name} <'e'|'d'> <c>");
-        eprintln!("example: {name} e 0x20");
-        exit(1)
-    });
-    let lzs = Lzs::new(args.c);
+    let all_args: Vec<_> = std::env::args().collect();
+    let subcommand_result = match all_args.get(1).map(String::as_str) {
+        Some("ff8") => Some(ff8::run(&all_args[2..])),
+        Some("complzss") => Some(complzss::run(&all_args[2..])),
+        Some("dump") => Some(dump::run(&all_args[2..])),
+        Some("detect") => Some(detect::run(&all_args[2..])),
+        Some("scan") => Some(scan::run(&all_args[2..])),
+        _ => None,
+    };
+    if let Some(result) = subcommand_result {
+        if let Err(err) = result {
+            eprintln!("error: {err}");
+            exit(1)
+        }
+        return;
+    }
+    let args = parse_args().unwrap_or_else(|err| usage(err));
+    let lzs = Lzs::new(args.c).with_level(args.level);
     let mut stdin = stdin();
     let mut stdout = stdout();
     let i_cnt = Rc::new(RefCell::new(0));
@@ -0,0 +1,87 @@
+use crate::detect::header;
+use crate::usage;
+use lzs::{Hit, Scanner};
+use std::fs;
+use std::path::Path;
+
+// Subcommand finding and extracting the compressed streams in a larger file.
+
+struct Options {
+    min_score: Option<u32>,
+    extract: Option<String>,
+    path: String,
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        min_score: None,
+        extract: None,
+        path: String::new(),
+    };
+    let mut args = args.iter();
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--min-score" => {
+                let score = args.next().ok_or("missing value of --min-score")?;
+                options.min_score = Some(score.parse().map_err(|_| "can't read the score")?);
+            }
+            "--extract" => {
+                let dir = args.next().ok_or("missing value of --extract")?;
+                options.extract = Some(dir.clone());
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if path.is_none() => path = Some(arg.clone()),
+            _ => return Err("more than one file".to_string()),
+        }
+    }
+    options.path = path.ok_or("missing file")?;
+    Ok(options)
+}
+
+fn line(hit: &Hit) -> String {
+    let detection = &hit.detection;
+    let c = detection
+        .c
+        .map_or_else(|| "-".to_string(), |c| format!("0x{c:02x}"));
+    format!(
+        "0x{:08x}  {:10}  {:12}  {:5}  {:4}  {:12}",
+        hit.offset,
+        hit.range().len(),
+        header(detection.dialect.header()),
+        detection.score,
+        c,
+        detection.decompressed,
+    )
+}
+
+fn extract(dir: &str, input: &[u8], hits: &[Hit]) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|err| format!("can't create {dir}: {err}"))?;
+    for hit in hits {
+        let path = Path::new(dir).join(format!("{:08x}.bin", hit.offset));
+        let content = hit.decompress(input).unwrap_or_default();
+        fs::write(&path, content)
+            .map_err(|err| format!("can't write {}: {err}", path.display()))?;
+    }
+    Ok(())
+}
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let options = parse_options(args).unwrap_or_else(|err| usage(&err));
+    let input =
+        fs::read(&options.path).map_err(|err| format!("can't read {}: {err}", options.path))?;
+    let mut scanner = Scanner::new();
+    if let Some(min_score) = options.min_score {
+        scanner = scanner.with_min_score(min_score);
+    }
+    let hits = scanner.scan(&input);
+
+    println!("offset      length      header        score  c     decompressed");
+    for hit in &hits {
+        println!("{}", line(hit));
+    }
+    if let Some(dir) = &options.extract {
+        extract(dir, &input, &hits)?;
+    }
+    Ok(())
+}
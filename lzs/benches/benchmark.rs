@@ -1,7 +1,11 @@
-use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
-use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use lzs::{Lzs, MatchFinder, Parser, ResultLzsErrorVoidExt, SliceReader, VecWriter};
 
 const MY_DYN_LZS: Lzs = Lzs::new(0x20);
+const MY_LAZY_LZS: Lzs = Lzs::new(0x20).with_parser(Parser::Lazy);
+const MY_OPTIMAL_LZS: Lzs = Lzs::new(0x20).with_parser(Parser::Optimal);
+const MY_HASH_CHAIN_LZS: Lzs =
+    Lzs::new(0x20).with_match_finder(MatchFinder::HashChain { depth: 16 });
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("compress dyn example", |b| {
@@ -16,6 +20,55 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             BatchSize::SmallInput,
         )
     });
+    c.bench_function("compress lazy example", |b| {
+        b.iter_batched(
+            || {
+                (
+                    SliceReader::new(EXAMPLE_DATA),
+                    VecWriter::with_capacity(EXAMPLE_DATA.len()),
+                )
+            },
+            |(r, w)| MY_LAZY_LZS.compress(r, w).void_unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("compress optimal example", |b| {
+        b.iter_batched(
+            || {
+                (
+                    SliceReader::new(EXAMPLE_DATA),
+                    VecWriter::with_capacity(EXAMPLE_DATA.len()),
+                )
+            },
+            |(r, w)| MY_OPTIMAL_LZS.compress(r, w).void_unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("compress hash chain example", |b| {
+        b.iter_batched(
+            || {
+                (
+                    SliceReader::new(EXAMPLE_DATA),
+                    VecWriter::with_capacity(EXAMPLE_DATA.len()),
+                )
+            },
+            |(r, w)| MY_HASH_CHAIN_LZS.compress(r, w).void_unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("compress dyn repeated example", |b| {
+        let repeated = EXAMPLE_DATA.repeat(8);
+        b.iter_batched(
+            || {
+                (
+                    SliceReader::new(&repeated),
+                    VecWriter::with_capacity(repeated.len()),
+                )
+            },
+            |(r, w)| MY_DYN_LZS.compress(r, w).void_unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
     c.bench_function("decompress dyn example", |b| {
         b.iter_batched(
             || {
@@ -31,6 +84,82 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             BatchSize::SmallInput,
         )
     });
+    c.bench_function("decompress slice example", |b| {
+        let compressed = MY_DYN_LZS
+            .compress(
+                SliceReader::new(EXAMPLE_DATA),
+                VecWriter::with_capacity(EXAMPLE_DATA.len()),
+            )
+            .void_unwrap();
+        let mut output = [0; EXAMPLE_DATA.len()];
+        b.iter(|| {
+            MY_DYN_LZS
+                .decompress_slice(&compressed, &mut output)
+                .unwrap()
+        })
+    });
+}
+
+/// The match length of `F = 18`, like the eight-byte compare of the match finders.
+fn compare_words(buf: &[u8], r: usize, p: usize) -> (usize, i32) {
+    let mut i = 1;
+    while i + 8 <= 18 {
+        let word_r = u64::from_le_bytes(buf[r + i..r + i + 8].try_into().unwrap());
+        let word_p = u64::from_le_bytes(buf[p + i..p + i + 8].try_into().unwrap());
+        let diff = word_r ^ word_p;
+        if diff != 0 {
+            let shift = diff.trailing_zeros() & !7;
+            return (
+                i + (shift / 8) as usize,
+                (word_r >> shift) as u8 as i32 - (word_p >> shift) as u8 as i32,
+            );
+        }
+        i += 8;
+    }
+    while i < 18 {
+        let cmp = buf[r + i] as i32 - buf[p + i] as i32;
+        if cmp != 0 {
+            return (i, cmp);
+        }
+        i += 1;
+    }
+    (18, 0)
+}
+
+/// The match length of `F = 18`, comparing one byte at a time like before.
+fn compare_bytes(buf: &[u8], r: usize, p: usize) -> (usize, i32) {
+    for i in 1..18 {
+        let cmp = buf[r + i] as i32 - buf[p + i] as i32;
+        if cmp != 0 {
+            return (i, cmp);
+        }
+    }
+    (18, 0)
+}
+
+pub fn compare_benchmark(c: &mut Criterion) {
+    // pairs at short distances mostly differ early, the ones a copy apart match fully
+    let repeated = EXAMPLE_DATA.repeat(8);
+    let pairs: Vec<(usize, usize)> = [1, 7, 100, EXAMPLE_DATA.len()]
+        .into_iter()
+        .flat_map(|distance| (distance..repeated.len() - 18).map(move |r| (r, r - distance)))
+        .collect();
+    for (name, compare) in [
+        (
+            "compare words",
+            compare_words as fn(&[u8], usize, usize) -> (usize, i32),
+        ),
+        ("compare bytes", compare_bytes),
+    ] {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                pairs
+                    .iter()
+                    .map(|&(r, p)| compare(black_box(&repeated), r, p).0)
+                    .sum::<usize>()
+            })
+        });
+    }
 }
 
 const EXAMPLE_DATA: &[u8; 781] = br#"
@@ -64,5 +193,5 @@ void decode(void)
 }
 "#;
 
-criterion_group!(benches, criterion_benchmark);
+criterion_group!(benches, criterion_benchmark, compare_benchmark);
 criterion_main!(benches);
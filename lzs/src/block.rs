@@ -0,0 +1,828 @@
+use crate::dynamic::{Decoder, Lzs};
+#[cfg(feature = "alloc")]
+use crate::error::ContainerWriteError;
+use crate::error::{ContainerError, LzsError};
+use crate::read_write::{Read, Write};
+use crate::slice::SliceReader;
+#[cfg(feature = "parallel")]
+use crate::vec::VecWriter;
+#[cfg(feature = "parallel")]
+use crate::void::ResultLzsErrorVoidExt;
+use crate::void::ResultLzsErrorVoidReadExt;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+use core::ops::Range;
+#[cfg(feature = "parallel")]
+use void::ResultVoidExt;
+
+/// Independently compressed blocks with an index for random access.
+///
+/// The input is split into blocks of a fixed size, each compressed into a separate stream
+/// (thus the window is reset for every block). After the streams follows the index, with the
+/// end offset of each stream, and a footer with the block size, the uncompressed length, the
+/// amount of blocks and the signature `LZSB` (all values 32-bit little-endian).
+///
+/// Decoding a range only decodes the blocks which cover it.
+///
+/// With [`compress_blocks_primed`](Lzs::compress_blocks_primed) the window of each block starts
+/// with the preceding 4 KiB instead, which compresses almost as well as a single stream. Every
+/// `interval`th block starts a new window (only the first one if zero), decoding a range
+/// decodes the blocks from the last of them. The signature is `LZSP` and the footer has the
+/// interval before it.
+///
+/// ```rust
+/// # use lzs::{Blocks, Lzs, ResultLzsErrorVoidExt, SliceReader, SliceWriter, VecWriter};
+/// let lzs = Lzs::new(0x20);
+/// let input = b"Example Data, Example Data, Example Data";
+/// let data = lzs
+///     .compress_blocks(16, SliceReader::new(input), VecWriter::with_capacity(64))
+///     .unwrap();
+///
+/// let blocks = Blocks::parse(&data).unwrap();
+/// assert_eq!(blocks.len(), 40);
+/// assert_eq!(blocks.block_count(), 3);
+/// let mut output = [0; 12];
+/// let result = blocks.read_range(lzs, 14..26, SliceWriter::new(&mut output));
+/// assert_eq!(result, Ok(12));
+/// assert_eq!(&output, b"Example Data");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Blocks<'a> {
+    block_size: usize,
+    len: usize,
+    primed: bool,
+    interval: usize,
+    streams: &'a [u8],
+    index: &'a [u8],
+}
+
+impl<'a> Blocks<'a> {
+    /// The signature at the end of the footer.
+    pub const SIGNATURE: &'static [u8; 4] = b"LZSB";
+    /// The signature at the end of the footer, when the blocks are primed.
+    pub const PRIMED_SIGNATURE: &'static [u8; 4] = b"LZSP";
+    /// The length of the footer.
+    pub const FOOTER_LEN: usize = 16;
+    /// The length of the footer, when the blocks are primed.
+    pub const PRIMED_FOOTER_LEN: usize = 20;
+
+    /// Parse the container.
+    pub fn parse(data: &'a [u8]) -> Result<Blocks<'a>, ContainerError> {
+        if data.len() < Self::FOOTER_LEN {
+            return Err(ContainerError::MissingHeader);
+        }
+        let primed = if data.ends_with(Self::SIGNATURE) {
+            false
+        } else if data.ends_with(Self::PRIMED_SIGNATURE) {
+            true
+        } else {
+            return Err(ContainerError::InvalidSignature);
+        };
+        let footer_len = if primed {
+            Self::PRIMED_FOOTER_LEN
+        } else {
+            Self::FOOTER_LEN
+        };
+        if data.len() < footer_len {
+            return Err(ContainerError::MissingHeader);
+        }
+        let (rest, footer) = data.split_at(data.len() - footer_len);
+        let block_size = read_u32(footer, 0);
+        let len = read_u32(footer, 1);
+        let block_count = read_u32(footer, 2);
+        let interval = if primed { read_u32(footer, 3) } else { 1 };
+        if block_count.saturating_mul(4) > rest.len() {
+            return Err(ContainerError::Truncated {
+                declared: block_count.saturating_mul(4),
+                available: rest.len(),
+            });
+        }
+        let (streams, index) = rest.split_at(rest.len() - block_count * 4);
+        let blocks = Blocks {
+            block_size,
+            len,
+            primed,
+            interval,
+            streams,
+            index,
+        };
+        if block_size == 0 || len.div_ceil(block_size) != block_count {
+            return Err(ContainerError::InvalidIndex);
+        }
+        let mut start = 0;
+        for i in 0..block_count {
+            let end = read_u32(index, i);
+            if end < start {
+                return Err(ContainerError::InvalidIndex);
+            }
+            start = end;
+        }
+        if start != streams.len() {
+            return Err(ContainerError::InvalidIndex);
+        }
+        Ok(blocks)
+    }
+
+    /// The length of the uncompressed data.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the uncompressed data is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The uncompressed size of each block (except the last one, which can be shorter).
+    #[inline]
+    #[must_use]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Whether the window of each block starts with the preceding data.
+    #[inline]
+    #[must_use]
+    pub fn is_primed(&self) -> bool {
+        self.primed
+    }
+
+    /// The distance of the blocks starting a new window, which can be decoded without the ones
+    /// before (one if the blocks are not primed, zero if only the first one).
+    #[inline]
+    #[must_use]
+    pub fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// The last block starting a new window, at or before block `i`.
+    #[inline]
+    fn window_start(&self, i: usize) -> usize {
+        match self.interval {
+            0 => 0,
+            interval => i - i % interval,
+        }
+    }
+
+    /// The amount of blocks.
+    #[inline]
+    #[must_use]
+    pub fn block_count(&self) -> usize {
+        self.index.len() / 4
+    }
+
+    /// The compressed stream of a block.
+    #[must_use]
+    pub fn block(&self, i: usize) -> &'a [u8] {
+        let start = if i == 0 {
+            0
+        } else {
+            read_u32(self.index, i - 1)
+        };
+        &self.streams[start..read_u32(self.index, i)]
+    }
+
+    /// The uncompressed range of a block.
+    #[must_use]
+    pub fn block_range(&self, i: usize) -> Range<usize> {
+        let start = i * self.block_size;
+        start..(start + self.block_size).min(self.len)
+    }
+
+    /// Decompress a range of the uncompressed data into the output.
+    ///
+    /// Only the blocks which cover the range are decompressed (and the ones before it since the
+    /// last block starting a new window, if the blocks are primed). A range outside of the data,
+    /// or a block which does not decompress to its size, results in a read error.
+    pub fn read_range<W: Write>(
+        &self,
+        lzs: Lzs,
+        range: Range<usize>,
+        mut writer: W,
+    ) -> Result<W::Output, LzsError<ContainerError, W::Error>> {
+        if range.end > self.len {
+            return Err(LzsError::ReadError(ContainerError::Truncated {
+                declared: range.end,
+                available: self.len,
+            }));
+        }
+        if range.start < range.end {
+            let first = self.window_start(range.start / self.block_size);
+            let mut history = History::new();
+            for i in first..range.end.div_ceil(self.block_size) {
+                let block_range = self.block_range(i);
+                let mut decoder = lzs.decoder();
+                if self.window_start(i) == i {
+                    history = History::new();
+                } else {
+                    history.prime(&mut decoder);
+                }
+                let mut window = RangeWriter {
+                    writer: &mut writer,
+                    skip: range.start.saturating_sub(block_range.start),
+                    take: range
+                        .end
+                        .min(block_range.end)
+                        .saturating_sub(range.start.max(block_range.start)),
+                    count: 0,
+                    history: self.primed.then_some(&mut history),
+                };
+                let mut reader = SliceReader::new(self.block(i));
+                while decoder
+                    .step(&mut reader, &mut window)
+                    .void_read_unwrap()
+                    .map_err(LzsError::WriteError)?
+                {}
+                if window.count != block_range.len() {
+                    return Err(LzsError::ReadError(ContainerError::SizeMismatch {
+                        expected: block_range.len(),
+                        actual: window.count,
+                    }));
+                }
+            }
+        }
+        writer.finish().map_err(LzsError::WriteError)
+    }
+}
+
+#[inline]
+fn read_u32(data: &[u8], i: usize) -> usize {
+    let data = &data[4 * i..4 * i + 4];
+    u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize
+}
+
+#[cfg(feature = "alloc")]
+/// Write the index and the footer, with the interval of primed blocks.
+fn write_index<W: Write>(
+    writer: &mut W,
+    index: &[usize],
+    block_size: usize,
+    len: usize,
+    interval: Option<usize>,
+) -> Result<(), ContainerWriteError<W::Error>> {
+    for value in index.iter().chain([block_size, len, index.len()].iter()) {
+        write_u32(writer, *value)?;
+    }
+    if let Some(interval) = interval {
+        write_u32(writer, interval)?;
+    }
+    let signature = if interval.is_some() {
+        Blocks::PRIMED_SIGNATURE
+    } else {
+        Blocks::SIGNATURE
+    };
+    for c in signature {
+        writer.write(*c).map_err(ContainerWriteError::Write)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+fn write_u32<W: Write>(writer: &mut W, value: usize) -> Result<(), ContainerWriteError<W::Error>> {
+    for c in ContainerError::u32_field(value)?.to_le_bytes() {
+        writer.write(c).map_err(ContainerWriteError::Write)?;
+    }
+    Ok(())
+}
+
+/// The last written bytes, which start the window of a primed block.
+struct History {
+    buffer: [u8; Lzs::n()],
+    len: usize,
+}
+
+impl History {
+    fn new() -> Self {
+        History {
+            buffer: [0; Lzs::n()],
+            len: 0,
+        }
+    }
+
+    #[inline(always)]
+    fn push(&mut self, c: u8) {
+        self.buffer[self.len % Lzs::n()] = c;
+        self.len += 1;
+    }
+
+    fn prime(&self, decoder: &mut Decoder) {
+        if self.len < Lzs::n() {
+            decoder.prime(&self.buffer[..self.len]);
+        } else {
+            let (end, start) = self.buffer.split_at(self.len % Lzs::n());
+            decoder.prime(start);
+            decoder.prime(end);
+        }
+    }
+}
+
+/// Passes only a range of the written bytes, and counts (and keeps) all of them.
+struct RangeWriter<'a, W> {
+    writer: &'a mut W,
+    skip: usize,
+    take: usize,
+    count: usize,
+    history: Option<&'a mut History>,
+}
+
+impl<W: Write> Write for RangeWriter<'_, W> {
+    type Output = ();
+    type Error = W::Error;
+    #[inline(always)]
+    fn write(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.count += 1;
+        if let Some(history) = &mut self.history {
+            history.push(data);
+        }
+        if self.skip > 0 {
+            self.skip -= 1;
+            Ok(())
+        } else if self.take > 0 {
+            self.take -= 1;
+            self.writer.write(data)
+        } else {
+            Ok(())
+        }
+    }
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Reads up to `remaining` bytes.
+#[cfg(feature = "alloc")]
+struct TakeReader<'a, R> {
+    reader: &'a mut R,
+    remaining: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<R: Read> Read for TakeReader<'_, R> {
+    type Error = R::Error;
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<u8>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        let result = self.reader.read()?;
+        if result.is_some() {
+            self.remaining -= 1;
+        }
+        Ok(result)
+    }
+}
+
+/// Counts the written bytes.
+#[cfg(feature = "alloc")]
+struct CountWriter<'a, W> {
+    writer: &'a mut W,
+    count: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<W: Write> Write for CountWriter<'_, W> {
+    type Output = ();
+    type Error = W::Error;
+    #[inline(always)]
+    fn write(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.count += 1;
+        self.writer.write(data)
+    }
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Lzs {
+    /// Compress the input data into independently compressed blocks with an index.
+    ///
+    /// See [`Blocks`](crate::Blocks) for the format, the index is collected on the heap. If the
+    /// compressed data or the block size reaches 4 GiB, [`ContainerError::TooLarge`] is returned
+    /// instead of the index. A block size of zero is rejected with
+    /// [`ContainerError::ZeroBlockSize`] before anything is read.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[allow(clippy::type_complexity)]
+    pub fn compress_blocks<R: Read, W: Write>(
+        &self,
+        block_size: usize,
+        mut reader: R,
+        mut writer: W,
+    ) -> Result<W::Output, LzsError<R::Error, ContainerWriteError<W::Error>>> {
+        if block_size == 0 {
+            return Err(LzsError::WriteError(ContainerError::ZeroBlockSize.into()));
+        }
+        let mut len = 0;
+        let mut index = Vec::new();
+        let mut output = CountWriter {
+            writer: &mut writer,
+            count: 0,
+        };
+        loop {
+            let mut input = TakeReader {
+                reader: &mut reader,
+                remaining: block_size,
+            };
+            self.compress_internal(&mut input, &mut output)
+                .map_err(|error| error.map_write_error(ContainerWriteError::Write))?;
+            let read = block_size - input.remaining;
+            if read > 0 {
+                len += read;
+                index.push(output.count);
+            }
+            if read < block_size {
+                break;
+            }
+        }
+        write_index(&mut writer, &index, block_size, len, None).map_err(LzsError::WriteError)?;
+        writer
+            .finish()
+            .map_err(|error| LzsError::WriteError(ContainerWriteError::Write(error)))
+    }
+
+    /// Compress the input data into blocks like [`compress_blocks`](Lzs::compress_blocks), but
+    /// the window of each block starts with the preceding 4 KiB of the input.
+    ///
+    /// Every `interval`th block starts a new window, without the input before it. The blocks
+    /// are still compressed separately, but can only be decompressed in order from such a block,
+    /// thus with an `interval` of zero (only the first block starts a new window) the result can
+    /// only be read sequentially. Each block is read into memory first.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[allow(clippy::type_complexity)]
+    pub fn compress_blocks_primed<R: Read, W: Write>(
+        &self,
+        block_size: usize,
+        interval: usize,
+        mut reader: R,
+        mut writer: W,
+    ) -> Result<W::Output, LzsError<R::Error, ContainerWriteError<W::Error>>> {
+        if block_size == 0 {
+            return Err(LzsError::WriteError(ContainerError::ZeroBlockSize.into()));
+        }
+        let mut len = 0;
+        let mut index = Vec::new();
+        let mut history = Vec::new();
+        let mut block = Vec::new();
+        let mut output = CountWriter {
+            writer: &mut writer,
+            count: 0,
+        };
+        loop {
+            block.clear();
+            while block.len() < block_size {
+                if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
+                    block.push(c);
+                } else {
+                    break;
+                }
+            }
+            if block.is_empty() {
+                break;
+            }
+            if interval != 0 && index.len() % interval == 0 {
+                history.clear();
+            }
+            self.compress_primed_internal(&history, &mut SliceReader::new(&block), &mut output)
+                .void_read_unwrap()
+                .map_err(|error| LzsError::WriteError(ContainerWriteError::Write(error)))?;
+            len += block.len();
+            index.push(output.count);
+            history.extend_from_slice(&block);
+            history.drain(..history.len().saturating_sub(Self::n()));
+            if block.len() < block_size {
+                break;
+            }
+        }
+        write_index(&mut writer, &index, block_size, len, Some(interval))
+            .map_err(LzsError::WriteError)?;
+        writer
+            .finish()
+            .map_err(|error| LzsError::WriteError(ContainerWriteError::Write(error)))
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl Lzs {
+    /// Compress the input data into blocks like [`compress_blocks`](Lzs::compress_blocks), with
+    /// the blocks distributed over `threads` threads (zero for the available parallelism).
+    ///
+    /// The output is the same for any amount of threads.
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let lzs = Lzs::new(0x20);
+    /// let input = b"Example Data, Example Data, Example Data";
+    /// let data = lzs.compress_blocks_parallel(16, input, 2, VecWriter::with_capacity(64));
+    /// let expected = lzs
+    ///     .compress_blocks(16, SliceReader::new(input), VecWriter::with_capacity(64))
+    ///     .unwrap();
+    /// assert_eq!(data, Ok(expected));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+    pub fn compress_blocks_parallel<W: Write>(
+        &self,
+        block_size: usize,
+        input: &[u8],
+        threads: usize,
+        writer: W,
+    ) -> Result<W::Output, ContainerWriteError<W::Error>> {
+        self.compress_blocks_threads(block_size, None, input, threads, writer)
+    }
+
+    /// Compress the input data into primed blocks like
+    /// [`compress_blocks_primed`](Lzs::compress_blocks_primed), with the blocks distributed over
+    /// `threads` threads (zero for the available parallelism).
+    ///
+    /// The output is the same for any amount of threads.
+    #[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+    pub fn compress_blocks_primed_parallel<W: Write>(
+        &self,
+        block_size: usize,
+        interval: usize,
+        input: &[u8],
+        threads: usize,
+        writer: W,
+    ) -> Result<W::Output, ContainerWriteError<W::Error>> {
+        self.compress_blocks_threads(block_size, Some(interval), input, threads, writer)
+    }
+
+    /// Compress the blocks, primed if there is an interval.
+    fn compress_blocks_threads<W: Write>(
+        self,
+        block_size: usize,
+        interval: Option<usize>,
+        input: &[u8],
+        threads: usize,
+        mut writer: W,
+    ) -> Result<W::Output, ContainerWriteError<W::Error>> {
+        if block_size == 0 {
+            return Err(ContainerError::ZeroBlockSize.into());
+        }
+        let threads = if threads == 0 {
+            std::thread::available_parallelism().map_or(1, core::num::NonZeroUsize::get)
+        } else {
+            threads
+        };
+        let block_count = input.len().div_ceil(block_size);
+        let compress_block = |i: usize| {
+            let start = i * block_size;
+            let block = &input[start..(start + block_size).min(input.len())];
+            let mut output = VecWriter::with_capacity(block.len());
+            if let Some(interval) = interval {
+                let window_start = if interval == 0 {
+                    0
+                } else {
+                    (i - i % interval) * block_size
+                };
+                let prefix = &input[start.saturating_sub(Self::n()).max(window_start)..start];
+                self.compress_primed_internal(prefix, &mut SliceReader::new(block), &mut output)
+                    .void_unwrap();
+            } else {
+                self.compress_internal(&mut SliceReader::new(block), &mut output)
+                    .void_unwrap();
+            }
+            output.finish().void_unwrap()
+        };
+
+        // Thread t compresses the blocks t, t + threads, ...
+        let mut streams = Vec::new();
+        streams.resize_with(block_count, Vec::new);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads.min(block_count))
+                .map(|t| {
+                    scope.spawn(move || {
+                        (t..block_count)
+                            .step_by(threads)
+                            .map(|i| (i, compress_block(i)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            for handle in handles {
+                for (i, stream) in handle.join().expect("compression does not panic") {
+                    streams[i] = stream;
+                }
+            }
+        });
+
+        let mut index = Vec::with_capacity(block_count);
+        let mut count = 0;
+        for stream in &streams {
+            for c in stream {
+                writer.write(*c).map_err(ContainerWriteError::Write)?;
+            }
+            count += stream.len();
+            index.push(count);
+        }
+        write_index(&mut writer, &index, block_size, input.len(), interval)?;
+        writer.finish().map_err(ContainerWriteError::Write)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::block::Blocks;
+    use crate::dynamic::Lzs;
+    use crate::error::{ContainerError, ContainerWriteError, LzsError};
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidWriteExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn compress(data: &[u8], block_size: usize) -> Vec<u8> {
+        TEST_LZS
+            .compress_blocks(
+                block_size,
+                SliceReader::new(data),
+                VecWriter::with_capacity(data.len()),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_read_range() {
+        let data = include_bytes!("dynamic/mod.rs");
+        let compressed = compress(data, 1000);
+        let blocks = Blocks::parse(&compressed).unwrap();
+        assert_eq!(blocks.len(), data.len());
+        assert_eq!(blocks.block_count(), data.len().div_ceil(1000));
+        for range in [0..data.len(), 0..0, 999..1001, 2000..3000, 4321..4567] {
+            let output = blocks
+                .read_range(TEST_LZS, range.clone(), VecWriter::with_capacity(0))
+                .void_write_unwrap()
+                .unwrap();
+            assert_eq!(output.as_slice(), &data[range]);
+        }
+        assert_eq!(
+            blocks
+                .read_range(TEST_LZS, 0..data.len() + 1, VecWriter::with_capacity(0))
+                .void_write_unwrap(),
+            Err(ContainerError::Truncated {
+                declared: data.len() + 1,
+                available: data.len()
+            })
+        );
+    }
+
+    #[test]
+    fn test_block_boundaries() {
+        for len in [0, 1, 99, 100, 101, 300] {
+            let data = vec![7u8; len];
+            let compressed = compress(&data, 100);
+            let blocks = Blocks::parse(&compressed).unwrap();
+            assert_eq!(blocks.block_count(), len.div_ceil(100));
+            let output = blocks
+                .read_range(TEST_LZS, 0..len, VecWriter::with_capacity(len))
+                .void_write_unwrap();
+            assert_eq!(output, Ok(data));
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut compressed = compress(b"Sample   Data   11221233123", 10);
+        let last = compressed.len() - 1;
+        compressed[last] = b'?';
+        assert_eq!(
+            Blocks::parse(&compressed),
+            Err(ContainerError::InvalidSignature)
+        );
+        compressed[last] = b'B';
+        compressed[0] ^= 0x01;
+        let blocks = Blocks::parse(&compressed).unwrap();
+        assert!(matches!(
+            blocks.read_range(TEST_LZS, 0..5, VecWriter::with_capacity(0)),
+            Err(LzsError::ReadError(ContainerError::SizeMismatch { .. }))
+        ));
+        compressed.remove(0);
+        assert_eq!(
+            Blocks::parse(&compressed),
+            Err(ContainerError::InvalidIndex)
+        );
+
+        let zero = Err(LzsError::WriteError(ContainerWriteError::Container(
+            ContainerError::ZeroBlockSize,
+        )));
+        assert_eq!(
+            TEST_LZS.compress_blocks(0, SliceReader::new(b"Sample"), VecWriter::with_capacity(0)),
+            zero
+        );
+        assert_eq!(
+            TEST_LZS.compress_blocks_primed(
+                0,
+                2,
+                SliceReader::new(b"Sample"),
+                VecWriter::with_capacity(0)
+            ),
+            zero
+        );
+    }
+
+    fn compress_primed(data: &[u8], block_size: usize, interval: usize) -> Vec<u8> {
+        TEST_LZS
+            .compress_blocks_primed(
+                block_size,
+                interval,
+                SliceReader::new(data),
+                VecWriter::with_capacity(data.len()),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_primed() {
+        let data = include_bytes!("dynamic/mod.rs");
+        for interval in [0, 1, 3] {
+            let compressed = compress_primed(data, 1000, interval);
+            if interval != 1 {
+                // the window is not reset for every block
+                assert!(compressed.len() < compress(data, 1000).len() * 9 / 10);
+            }
+            let blocks = Blocks::parse(&compressed).unwrap();
+            assert!(blocks.is_primed());
+            assert_eq!(blocks.interval(), interval);
+            assert_eq!(blocks.block_count(), data.len().div_ceil(1000));
+            for range in [0..data.len(), 0..0, 999..1001, 6000..7000, 9876..9999] {
+                let output = blocks
+                    .read_range(TEST_LZS, range.clone(), VecWriter::with_capacity(0))
+                    .void_write_unwrap()
+                    .unwrap();
+                assert_eq!(output.as_slice(), &data[range]);
+            }
+        }
+        // with every block starting a window, the streams are the same as without priming
+        let primed = compress_primed(data, 1000, 1);
+        let unprimed = compress(data, 1000);
+        let (primed, unprimed) = (
+            Blocks::parse(&primed).unwrap(),
+            Blocks::parse(&unprimed).unwrap(),
+        );
+        assert!((0..primed.block_count()).all(|i| primed.block(i) == unprimed.block(i)));
+    }
+
+    #[test]
+    fn test_primed_resync() {
+        // a damaged block only affects the ones until the next block starting a window
+        let data = include_bytes!("dynamic/mod.rs");
+        let mut compressed = compress_primed(data, 1000, 3);
+        let second = Blocks::parse(&compressed).unwrap().block(0).len();
+        compressed[second] ^= 0xFF;
+        let blocks = Blocks::parse(&compressed).unwrap();
+        let output = blocks
+            .read_range(TEST_LZS, 6000..8000, VecWriter::with_capacity(0))
+            .void_write_unwrap()
+            .unwrap();
+        assert_eq!(output.as_slice(), &data[6000..8000]);
+        assert!(blocks
+            .read_range(TEST_LZS, 1000..2000, VecWriter::with_capacity(0))
+            .void_write_unwrap()
+            .map_or(true, |output| output != data[1000..2000]));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel() {
+        let data = include_bytes!("dynamic/mod.rs");
+        for block_size in [100, 1000, 5000, data.len(), 100_000] {
+            let expected = compress(data, block_size);
+            let expected_primed =
+                [0, 2].map(|interval| compress_primed(data, block_size, interval));
+            for threads in [0, 1, 2, 3, 16] {
+                let output = TEST_LZS.compress_blocks_parallel(
+                    block_size,
+                    data,
+                    threads,
+                    VecWriter::with_capacity(data.len()),
+                );
+                assert_eq!(output.unwrap(), expected);
+                for (interval, expected) in [0, 2].into_iter().zip(&expected_primed) {
+                    let output = TEST_LZS.compress_blocks_primed_parallel(
+                        block_size,
+                        interval,
+                        data,
+                        threads,
+                        VecWriter::with_capacity(data.len()),
+                    );
+                    assert_eq!(&output.unwrap(), expected);
+                }
+            }
+        }
+        let zero = Err(ContainerWriteError::Container(
+            ContainerError::ZeroBlockSize,
+        ));
+        assert_eq!(
+            TEST_LZS.compress_blocks_parallel(0, data, 2, VecWriter::with_capacity(0)),
+            zero
+        );
+        assert_eq!(
+            TEST_LZS.compress_blocks_primed_parallel(0, 2, data, 2, VecWriter::with_capacity(0)),
+            zero
+        );
+    }
+}
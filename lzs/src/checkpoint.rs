@@ -0,0 +1,519 @@
+use crate::dynamic::{Decoder, Lzs};
+use crate::error::{ContainerError, LzsError};
+#[cfg(feature = "std")]
+use crate::io_simple::IOSimpleReader;
+use crate::read_write::{Read, Write};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use void::{unreachable, Void};
+
+/// A snapshot of the decoder state between two units, see [`CheckpointIndex`](crate::CheckpointIndex).
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Clone)]
+pub struct Checkpoint {
+    input: usize,
+    output: usize,
+    decoder: Decoder,
+}
+
+impl Checkpoint {
+    const LEN: usize = 8 + 8 + 2 + 2 + Lzs::n();
+
+    /// The offset in the compressed stream.
+    #[inline]
+    #[must_use]
+    pub fn input(&self) -> usize {
+        self.input
+    }
+
+    /// The offset in the decompressed data.
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> usize {
+        self.output
+    }
+}
+
+/// Checkpoints of the decoder state for seeking in a plain stream.
+///
+/// The index is built by decoding the whole stream once, at the given interval (in decompressed
+/// bytes) the ring buffer, the flags and the in- and output offsets are recorded.
+/// A decoder can then resume from the nearest checkpoint, see
+/// [`CheckpointReader`](crate::CheckpointReader).
+///
+/// Every checkpoint needs `1 << EI` bytes, the index can be saved with
+/// [`write_to`](CheckpointIndex::write_to) and loaded with [`parse`](CheckpointIndex::parse).
+///
+/// ```rust
+/// # use lzs::{CheckpointIndex, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+/// # use void::ResultVoidExt;
+/// let lzs = Lzs::new(0x20);
+/// let compressed = lzs
+///     .compress(SliceReader::new(&[42; 10000]), VecWriter::with_capacity(1000))
+///     .void_unwrap();
+/// let index = CheckpointIndex::build(lzs, 4096, SliceReader::new(&compressed)).void_unwrap();
+/// assert_eq!(index.len(), 10000);
+/// assert_eq!(index.checkpoints().len(), 3);
+///
+/// let saved = index.write_to(VecWriter::with_capacity(0)).void_unwrap();
+/// assert_eq!(CheckpointIndex::parse(&saved).unwrap().len(), 10000);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Clone)]
+pub struct CheckpointIndex {
+    input_len: usize,
+    len: usize,
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl CheckpointIndex {
+    /// The signature at the start of a saved index.
+    pub const SIGNATURE: &'static [u8; 4] = b"LZSI";
+    const HEADER_LEN: usize = 4 + 8 + 8 + 8;
+
+    /// Build the index by decoding the whole stream.
+    ///
+    /// Checkpoints are only recorded between two units, thus the distance between two
+    /// checkpoints may exceed the interval by up to `F - 1` bytes.
+    ///
+    /// # Panics
+    /// If the interval is zero.
+    pub fn build<R: Read>(
+        lzs: Lzs,
+        interval: usize,
+        mut reader: R,
+    ) -> Result<CheckpointIndex, R::Error> {
+        assert!(interval > 0, "interval must not be zero");
+        let mut reader = CountReader {
+            reader: &mut reader,
+            count: 0,
+        };
+        let mut writer = CountWriter(0);
+        let mut decoder = lzs.decoder();
+        let mut checkpoints = Vec::new();
+        let mut next = 0;
+        loop {
+            if writer.0 >= next {
+                checkpoints.push(Checkpoint {
+                    input: reader.count,
+                    output: writer.0,
+                    decoder: decoder.clone(),
+                });
+                next = writer.0 + interval;
+            }
+            match decoder.step(&mut reader, &mut writer) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(LzsError::ReadError(e)) => return Err(e),
+                Err(LzsError::WriteError(e)) => unreachable(e),
+            }
+        }
+        Ok(CheckpointIndex {
+            input_len: reader.count,
+            len: writer.0,
+            checkpoints,
+        })
+    }
+
+    /// The length of the compressed stream.
+    #[inline]
+    #[must_use]
+    pub fn input_len(&self) -> usize {
+        self.input_len
+    }
+
+    /// The length of the decompressed data.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the decompressed data is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// All checkpoints, ordered by their offsets.
+    ///
+    /// The first checkpoint is always at the start of the stream.
+    #[inline]
+    #[must_use]
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// The last checkpoint before or at the offset in the decompressed data.
+    #[must_use]
+    pub fn nearest(&self, output: usize) -> &Checkpoint {
+        let i = self.checkpoints.partition_point(|c| c.output <= output);
+        &self.checkpoints[i.max(1) - 1]
+    }
+
+    /// Write the index into the output.
+    ///
+    /// All values are little-endian: the signature `LZSI`, the length of the compressed
+    /// stream, the length of the decompressed data and the amount of checkpoints as 64-bit
+    /// values, followed by the checkpoints: the in- and output offsets as 64-bit values,
+    /// the position in the ring buffer and the flags as 16-bit values and the ring buffer.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<W::Output, W::Error> {
+        let mut write = |data: &[u8]| data.iter().try_for_each(|c| writer.write(*c));
+        write(Self::SIGNATURE)?;
+        for value in [self.input_len, self.len, self.checkpoints.len()] {
+            write(&(value as u64).to_le_bytes())?;
+        }
+        for checkpoint in &self.checkpoints {
+            write(&(checkpoint.input as u64).to_le_bytes())?;
+            write(&(checkpoint.output as u64).to_le_bytes())?;
+            write(&(checkpoint.decoder.r as u16).to_le_bytes())?;
+            write(&(checkpoint.decoder.flags as u16).to_le_bytes())?;
+            write(&checkpoint.decoder.buffer)?;
+        }
+        writer.finish()
+    }
+
+    /// Load an index written by [`write_to`](CheckpointIndex::write_to).
+    pub fn parse(data: &[u8]) -> Result<CheckpointIndex, ContainerError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ContainerError::MissingHeader);
+        }
+        if !data.starts_with(Self::SIGNATURE) {
+            return Err(ContainerError::InvalidSignature);
+        }
+        let read_u64 = |offset: usize| {
+            let mut value = [0; 8];
+            value.copy_from_slice(&data[offset..offset + 8]);
+            usize::try_from(u64::from_le_bytes(value)).map_err(|_| ContainerError::InvalidIndex)
+        };
+        let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let input_len = read_u64(4)?;
+        let len = read_u64(12)?;
+        let count = read_u64(20)?;
+        let declared = count
+            .checked_mul(Checkpoint::LEN)
+            .and_then(|l| l.checked_add(Self::HEADER_LEN))
+            .ok_or(ContainerError::InvalidIndex)?;
+        if declared > data.len() {
+            return Err(ContainerError::Truncated {
+                declared,
+                available: data.len(),
+            });
+        }
+        let mut checkpoints = Vec::with_capacity(count);
+        for i in 0..count {
+            let offset = Self::HEADER_LEN + i * Checkpoint::LEN;
+            let mut checkpoint = Checkpoint {
+                input: read_u64(offset)?,
+                output: read_u64(offset + 8)?,
+                decoder: Lzs::new(0).decoder(),
+            };
+            checkpoint.decoder.r = read_u16(offset + 16) as usize;
+            checkpoint.decoder.flags = read_u16(offset + 18) as usize;
+            checkpoint
+                .decoder
+                .buffer
+                .copy_from_slice(&data[offset + 20..offset + Checkpoint::LEN]);
+            let valid = checkpoint.decoder.r < Lzs::n()
+                && checkpoint.input <= input_len
+                && checkpoint.output <= len
+                && checkpoints
+                    .last()
+                    .is_none_or(|c: &Checkpoint| c.output <= checkpoint.output);
+            if !valid {
+                return Err(ContainerError::InvalidIndex);
+            }
+            checkpoints.push(checkpoint);
+        }
+        if checkpoints.first().is_none_or(|c| c.output != 0) {
+            return Err(ContainerError::InvalidIndex);
+        }
+        Ok(CheckpointIndex {
+            input_len,
+            len,
+            checkpoints,
+        })
+    }
+}
+
+/// Counts the read bytes.
+struct CountReader<'a, R> {
+    reader: &'a mut R,
+    count: usize,
+}
+
+impl<R: Read> Read for CountReader<'_, R> {
+    type Error = R::Error;
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<u8>, Self::Error> {
+        let result = self.reader.read()?;
+        if result.is_some() {
+            self.count += 1;
+        }
+        Ok(result)
+    }
+}
+
+/// Counts and discards the written bytes.
+struct CountWriter(usize);
+
+impl Write for CountWriter {
+    type Output = usize;
+    type Error = Void;
+    #[inline(always)]
+    fn write(&mut self, _: u8) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+/// Collects the output of a single unit.
+#[cfg(feature = "std")]
+struct UnitWriter<'a> {
+    buffer: &'a mut [u8; Lzs::f()],
+    len: usize,
+}
+
+#[cfg(feature = "std")]
+impl Write for UnitWriter<'_> {
+    type Output = ();
+    type Error = Void;
+    #[inline(always)]
+    fn write(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.buffer[self.len] = data;
+        self.len += 1;
+        Ok(())
+    }
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok(())
+    }
+}
+
+/// A seekable reader of a plain stream, using a [`CheckpointIndex`](crate::CheckpointIndex).
+///
+/// Seeking resumes from the nearest checkpoint before the target (unless the current position
+/// is closer), and decodes up to the target. Seeking beyond the end stops at the end.
+///
+/// ```rust
+/// # use lzs::{CheckpointIndex, CheckpointReader, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+/// # use std::io::{Cursor, Read, Seek, SeekFrom};
+/// # use void::ResultVoidExt;
+/// let lzs = Lzs::new(0x20);
+/// let data: Vec<u8> = (0..10000u32).map(|i| (i / 100) as u8).collect();
+/// let compressed = lzs
+///     .compress(SliceReader::new(&data), VecWriter::with_capacity(1000))
+///     .void_unwrap();
+/// let index = CheckpointIndex::build(lzs, 1024, SliceReader::new(&compressed)).void_unwrap();
+///
+/// let mut reader = CheckpointReader::new(&index, Cursor::new(compressed)).unwrap();
+/// reader.seek(SeekFrom::Start(5050)).unwrap();
+/// let mut buf = [0; 100];
+/// reader.read_exact(&mut buf).unwrap();
+/// assert_eq!(buf, data[5050..5150]);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+pub struct CheckpointReader<'a, R: std::io::Read + std::io::Seek> {
+    index: &'a CheckpointIndex,
+    inner: std::io::BufReader<R>,
+    decoder: Decoder,
+    output: usize,
+    pending: [u8; Lzs::f()],
+    pending_len: usize,
+    pending_pos: usize,
+    eof: bool,
+}
+
+#[cfg(feature = "std")]
+impl<'a, R: std::io::Read + std::io::Seek> CheckpointReader<'a, R> {
+    /// Constructs a new reader, at the start of the stream.
+    pub fn new(index: &'a CheckpointIndex, inner: R) -> std::io::Result<CheckpointReader<'a, R>> {
+        let first = &index.checkpoints[0];
+        let mut reader = CheckpointReader {
+            index,
+            inner: std::io::BufReader::new(inner),
+            decoder: first.decoder.clone(),
+            output: first.output,
+            pending: [0; Lzs::f()],
+            pending_len: 0,
+            pending_pos: 0,
+            eof: false,
+        };
+        reader.restore(first)?;
+        Ok(reader)
+    }
+
+    /// The current offset in the decompressed data.
+    #[inline]
+    fn position(&self) -> usize {
+        self.output - self.pending_len + self.pending_pos
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint) -> std::io::Result<()> {
+        std::io::Seek::seek(
+            &mut self.inner,
+            std::io::SeekFrom::Start(checkpoint.input as u64),
+        )?;
+        self.decoder.clone_from(&checkpoint.decoder);
+        self.output = checkpoint.output;
+        self.pending_len = 0;
+        self.pending_pos = 0;
+        self.eof = false;
+        Ok(())
+    }
+
+    /// Decode the next unit into the pending buffer, returns `false` at the end.
+    fn fill(&mut self) -> std::io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let mut writer = UnitWriter {
+            buffer: &mut self.pending,
+            len: 0,
+        };
+        let more = match self
+            .decoder
+            .step(&mut IOSimpleReader::new(&mut self.inner), &mut writer)
+        {
+            Ok(more) => more,
+            Err(LzsError::ReadError(e)) => return Err(e),
+            Err(LzsError::WriteError(e)) => unreachable(e),
+        };
+        self.pending_len = writer.len;
+        self.pending_pos = 0;
+        self.output += writer.len;
+        self.eof = !more;
+        Ok(more)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + std::io::Seek> std::io::Read for CheckpointReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut len = 0;
+        while len < buf.len() {
+            if self.pending_pos == self.pending_len {
+                if !self.fill()? {
+                    break;
+                }
+                continue;
+            }
+            let n = (buf.len() - len).min(self.pending_len - self.pending_pos);
+            buf[len..len + n]
+                .copy_from_slice(&self.pending[self.pending_pos..self.pending_pos + n]);
+            self.pending_pos += n;
+            len += n;
+        }
+        Ok(len)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read + std::io::Seek> std::io::Seek for CheckpointReader<'_, R> {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let (base, offset) = match pos {
+            std::io::SeekFrom::Start(offset) => (0, offset as i128),
+            std::io::SeekFrom::End(offset) => (self.index.len, offset as i128),
+            std::io::SeekFrom::Current(offset) => (self.position(), offset as i128),
+        };
+        let target = usize::try_from(base as i128 + offset).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+        let checkpoint = self.index.nearest(target);
+        if target < self.position() || checkpoint.output > self.position() {
+            self.restore(checkpoint)?;
+        }
+        while self.position() < target {
+            if self.pending_pos == self.pending_len {
+                if !self.fill()? {
+                    break;
+                }
+            } else {
+                self.pending_pos +=
+                    (target - self.position()).min(self.pending_len - self.pending_pos);
+            }
+        }
+        Ok(self.position() as u64)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use crate::checkpoint::{CheckpointIndex, CheckpointReader};
+    use crate::dynamic::Lzs;
+    use crate::error::ContainerError;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use void::ResultVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn index(data: &[u8], interval: usize) -> (Vec<u8>, CheckpointIndex) {
+        let compressed = TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap();
+        let index =
+            CheckpointIndex::build(TEST_LZS, interval, SliceReader::new(&compressed)).void_unwrap();
+        (compressed, index)
+    }
+
+    #[test]
+    fn test_build() {
+        let data = include_bytes!("dynamic/mod.rs");
+        let (compressed, index) = index(data, 1000);
+        assert_eq!(index.len(), data.len());
+        assert_eq!(index.input_len(), compressed.len());
+        let checkpoints = index.checkpoints();
+        assert_eq!(checkpoints[0].output(), 0);
+        for pair in checkpoints.windows(2) {
+            let distance = pair[1].output() - pair[0].output();
+            assert!((1000..1000 + Lzs::f()).contains(&distance));
+        }
+        let saved = index.write_to(VecWriter::with_capacity(0)).void_unwrap();
+        let loaded = CheckpointIndex::parse(&saved).unwrap();
+        assert_eq!(loaded.len(), index.len());
+        assert_eq!(loaded.checkpoints().len(), checkpoints.len());
+        assert_eq!(
+            CheckpointIndex::parse(&saved[..saved.len() - 1]).err(),
+            Some(ContainerError::Truncated {
+                declared: saved.len(),
+                available: saved.len() - 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let data = include_bytes!("dynamic/mod.rs");
+        let (compressed, index) = index(data, 500);
+        let mut reader = CheckpointReader::new(&index, Cursor::new(compressed)).unwrap();
+        let mut buf = [0; 300];
+        for start in [4000, 0, 1234, 1235, 6000, 3000, data.len() - 300] {
+            assert_eq!(
+                reader.seek(SeekFrom::Start(start as u64)).unwrap(),
+                start as u64
+            );
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(&buf[..], &data[start..start + 300]);
+        }
+        assert_eq!(
+            reader.seek(SeekFrom::Current(-600)).unwrap(),
+            data.len() as u64 - 600
+        );
+        assert_eq!(reader.seek(SeekFrom::End(10)).unwrap(), data.len() as u64);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        let mut all = Vec::new();
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        reader.read_to_end(&mut all).unwrap();
+        assert_eq!(all, data);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::dynamic::Lzs;
+use crate::error::ContainerError;
+#[cfg(feature = "alloc")]
+use crate::{
+    error::ContainerWriteError, read_write::Write, slice::SliceReader, vec::VecWriter,
+    void::ResultLzsErrorVoidExt,
+};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// Compute the Adler-32 checksum.
+fn adler32(data: &[u8]) -> u32 {
+    const MOD: u32 = 65521;
+    // largest n with 255 * n * (n + 1) / 2 + (n + 1) * (MOD - 1) <= u32::MAX
+    const CHUNK: usize = 5552;
+    let mut a = 1u32;
+    let mut b = 0u32;
+    for chunk in data.chunks(CHUNK) {
+        for c in chunk {
+            a += *c as u32;
+            b += a;
+        }
+        a %= MOD;
+        b %= MOD;
+    }
+    (b << 16) | a
+}
+
+/// The `comp`/`lzss` container of the macOS and iOS kernelcache.
+///
+/// The big-endian header contains a signature, the Adler-32 checksum and the size of the
+/// uncompressed data, the size of the compressed stream and padding (including the platform
+/// name and root path), followed by the stream itself. The stream is compressed with
+/// `C = 0x20`, see [`LZS`](Complzss::LZS).
+///
+/// ```rust
+/// # use lzs::{Complzss, VecWriter};
+/// let file = Complzss::compress(b"Example Data", VecWriter::with_capacity(400)).unwrap();
+/// assert_eq!(&file[..8], b"complzss");
+///
+/// let container = Complzss::parse(&file).unwrap();
+/// assert_eq!(container.uncompressed_size(), 12);
+/// assert_eq!(container.decompress().unwrap(), b"Example Data");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Complzss<'a> {
+    adler32: u32,
+    uncompressed_size: usize,
+    platform_name: &'a [u8],
+    root_path: &'a [u8],
+    stream: &'a [u8],
+    trailing: &'a [u8],
+}
+
+impl<'a> Complzss<'a> {
+    /// The length of the header.
+    pub const HEADER_LEN: usize = 0x180;
+    /// The signature at the start of the header.
+    pub const SIGNATURE: &'static [u8; 8] = b"complzss";
+    /// The parameters used to de-/compress the stream.
+    pub const LZS: Lzs = Lzs::new(0x20);
+
+    const PLATFORM_NAME_OFFSET: usize = 0x40;
+    const ROOT_PATH_OFFSET: usize = 0x80;
+
+    /// Parse the container.
+    ///
+    /// Additional bytes after the stream are accepted, but an error is returned if the header
+    /// declares more bytes than available.
+    pub fn parse(data: &'a [u8]) -> Result<Complzss<'a>, ContainerError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ContainerError::MissingHeader);
+        }
+        let (header, rest) = data.split_at(Self::HEADER_LEN);
+        if !header.starts_with(Self::SIGNATURE) {
+            return Err(ContainerError::InvalidSignature);
+        }
+        let value =
+            |i: usize| u32::from_be_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+        let compressed_size = value(16) as usize;
+        if compressed_size > rest.len() {
+            return Err(ContainerError::Truncated {
+                declared: compressed_size,
+                available: rest.len(),
+            });
+        }
+        let (stream, trailing) = rest.split_at(compressed_size);
+        let c_string = |field: &'a [u8]| {
+            let len = field.iter().position(|c| *c == 0).unwrap_or(field.len());
+            &field[..len]
+        };
+        Ok(Complzss {
+            adler32: value(8),
+            uncompressed_size: value(12) as usize,
+            platform_name: c_string(&header[Self::PLATFORM_NAME_OFFSET..Self::ROOT_PATH_OFFSET]),
+            root_path: c_string(&header[Self::ROOT_PATH_OFFSET..]),
+            stream,
+            trailing,
+        })
+    }
+
+    /// The Adler-32 checksum of the uncompressed data.
+    #[inline]
+    #[must_use]
+    pub fn adler32(&self) -> u32 {
+        self.adler32
+    }
+
+    /// The size of the uncompressed data.
+    #[inline]
+    #[must_use]
+    pub fn uncompressed_size(&self) -> usize {
+        self.uncompressed_size
+    }
+
+    /// The platform name (without the terminating zeros).
+    #[inline]
+    #[must_use]
+    pub fn platform_name(&self) -> &'a [u8] {
+        self.platform_name
+    }
+
+    /// The root path (without the terminating zeros).
+    #[inline]
+    #[must_use]
+    pub fn root_path(&self) -> &'a [u8] {
+        self.root_path
+    }
+
+    /// The compressed stream (without the header).
+    #[inline]
+    #[must_use]
+    pub fn stream(&self) -> &'a [u8] {
+        self.stream
+    }
+
+    /// The bytes after the stream.
+    #[inline]
+    #[must_use]
+    pub fn trailing(&self) -> &'a [u8] {
+        self.trailing
+    }
+
+    /// Check the size and checksum of the uncompressed data against the header.
+    pub fn verify(&self, data: &[u8]) -> Result<(), ContainerError> {
+        if data.len() != self.uncompressed_size {
+            return Err(ContainerError::SizeMismatch {
+                expected: self.uncompressed_size,
+                actual: data.len(),
+            });
+        }
+        let actual = adler32(data);
+        if actual != self.adler32 {
+            return Err(ContainerError::ChecksumMismatch {
+                expected: self.adler32,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// Decompress and [`verify`](Complzss::verify) the stream.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[cfg(feature = "alloc")]
+    pub fn decompress(&self) -> Result<Vec<u8>, ContainerError> {
+        let data = Self::LZS
+            .decompress(
+                SliceReader::new(self.stream),
+                VecWriter::with_capacity(self.uncompressed_size),
+            )
+            .void_unwrap();
+        self.verify(&data)?;
+        Ok(data)
+    }
+
+    /// Compress the data into the output, including the header.
+    ///
+    /// The platform name and root path are left empty. Data or compressed data of 4 GiB or more
+    /// is returned as [`ContainerError::TooLarge`] and nothing is written.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[cfg(feature = "alloc")]
+    pub fn compress<W: Write>(
+        data: &[u8],
+        mut writer: W,
+    ) -> Result<W::Output, ContainerWriteError<W::Error>> {
+        let stream = Self::LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap();
+        let mut header = [0u8; Self::HEADER_LEN];
+        header[..8].copy_from_slice(Self::SIGNATURE);
+        let values = [
+            adler32(data),
+            ContainerError::u32_field(data.len())?,
+            ContainerError::u32_field(stream.len())?,
+        ];
+        for (i, value) in values.iter().enumerate() {
+            header[8 + 4 * i..12 + 4 * i].copy_from_slice(&value.to_be_bytes());
+        }
+        for c in header.iter().chain(stream.iter()) {
+            writer.write(*c).map_err(ContainerWriteError::Write)?;
+        }
+        writer.finish().map_err(ContainerWriteError::Write)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::complzss::{adler32, Complzss};
+    use crate::error::ContainerError;
+    use crate::vec::VecWriter;
+
+    const TEST_DATA: &[u8; 27] = b"Sample   Data   11221233123";
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        assert_eq!(adler32(&vec![0xFF; 100_000]), 0x149a_302c);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut data = Complzss::compress(TEST_DATA, VecWriter::with_capacity(0)).unwrap();
+        data[Complzss::PLATFORM_NAME_OFFSET..][..4].copy_from_slice(b"PPC\0");
+        data.push(0);
+        let container = Complzss::parse(&data).unwrap();
+        assert_eq!(container.platform_name(), b"PPC");
+        assert_eq!(container.root_path(), b"");
+        assert_eq!(container.stream().len(), 27);
+        assert_eq!(container.trailing(), &[0]);
+        assert_eq!(container.decompress().unwrap(), TEST_DATA);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut data = Complzss::compress(TEST_DATA, VecWriter::with_capacity(0)).unwrap();
+        assert_eq!(
+            Complzss::parse(&data[..data.len() - 1]),
+            Err(ContainerError::Truncated {
+                declared: 27,
+                available: 26
+            })
+        );
+        data[Complzss::HEADER_LEN + 1] ^= 1;
+        let container = Complzss::parse(&data).unwrap();
+        assert!(matches!(
+            container.decompress(),
+            Err(ContainerError::ChecksumMismatch { .. })
+        ));
+        data[0] = b'C';
+        assert_eq!(
+            Complzss::parse(&data),
+            Err(ContainerError::InvalidSignature)
+        );
+    }
+}
@@ -0,0 +1,658 @@
+use crate::dynamic::Lzs;
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::ops::RangeInclusive;
+
+/// The value of the initial fill in the decoded output, which is not known yet.
+const FILL: u16 = 256;
+/// The number of bytes of padding accepted after a stream with a compressed length header.
+const PADDING: usize = 16;
+
+/// A 32-bit little-endian length header before a compressed stream.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum LengthHeader {
+    /// No header.
+    None,
+    /// The length of the compressed stream, like in [`LengthPrefixed`](crate::LengthPrefixed).
+    Compressed,
+    /// The length of the decompressed data.
+    Decompressed,
+}
+
+/// The layout of an LZSS stream, which may differ from the one of this crate.
+///
+/// A stream has a flag byte before every eight units, the lowest bit first. A match is stored
+/// in two bytes: the low eight bits of the position, then its high bits followed by the length
+/// minus `THRESHOLD + 1` in the low `EJ` bits. The position has `EI = 16 - EJ` bits, so
+/// `N = 2^EI` and `F = 2^EJ + THRESHOLD`, with `THRESHOLD = 2`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Dialect {
+    position_bits: u8,
+    literal_flag: bool,
+    header: LengthHeader,
+}
+
+impl Dialect {
+    /// The layout of this crate, see [`Lzs`].
+    pub const LZS: Dialect = Dialect {
+        position_bits: Lzs::ei() as u8,
+        literal_flag: true,
+        header: LengthHeader::None,
+    };
+    /// The numbers of bits of a position tried by [`detect`](Dialect::detect).
+    pub const POSITION_BITS: RangeInclusive<u8> = 10..=13;
+
+    /// Create a layout, if the number of bits of a position is one of
+    /// [`POSITION_BITS`](Dialect::POSITION_BITS).
+    ///
+    /// ```rust
+    /// # use lzs::{Dialect, LengthHeader};
+    /// let dialect = Dialect::new(12, true, LengthHeader::None);
+    /// assert_eq!(dialect, Some(Dialect::LZS));
+    /// assert_eq!(Dialect::new(17, true, LengthHeader::None), None);
+    /// ```
+    #[must_use]
+    pub const fn new(position_bits: u8, literal_flag: bool, header: LengthHeader) -> Option<Self> {
+        if position_bits < *Self::POSITION_BITS.start()
+            || position_bits > *Self::POSITION_BITS.end()
+        {
+            return None;
+        }
+        Some(Dialect {
+            position_bits,
+            literal_flag,
+            header,
+        })
+    }
+
+    /// The same layout with another header.
+    #[inline]
+    #[must_use]
+    pub const fn with_header(self, header: LengthHeader) -> Self {
+        Dialect { header, ..self }
+    }
+
+    /// The number of bits of a position (`EI`).
+    #[inline]
+    #[must_use]
+    pub const fn position_bits(&self) -> u8 {
+        self.position_bits
+    }
+
+    /// The flag bit of literals, matches have the other value.
+    #[inline]
+    #[must_use]
+    pub const fn literal_flag(&self) -> bool {
+        self.literal_flag
+    }
+
+    /// The header before the stream.
+    #[inline]
+    #[must_use]
+    pub const fn header(&self) -> LengthHeader {
+        self.header
+    }
+
+    /// The number of bits of a length (`EJ`).
+    #[inline]
+    #[must_use]
+    pub const fn length_bits(&self) -> u8 {
+        16 - self.position_bits
+    }
+
+    /// The size of the buffer of the decoder (`N`).
+    #[inline]
+    #[must_use]
+    pub const fn n(&self) -> usize {
+        1 << self.position_bits
+    }
+
+    /// The maximum length of a match (`F`).
+    #[inline]
+    #[must_use]
+    pub const fn f(&self) -> usize {
+        (1 << self.length_bits()) + Lzs::threshold()
+    }
+
+    /// Whether the stream can be decompressed by [`Lzs`], after removing the header.
+    #[inline]
+    #[must_use]
+    pub const fn is_supported(&self) -> bool {
+        self.position_bits == Self::LZS.position_bits && self.literal_flag
+    }
+
+    /// Try the layouts on unknown data, and rank them by how well it decodes.
+    ///
+    /// Each number of [`POSITION_BITS`](Dialect::POSITION_BITS) is tried with both flag
+    /// polarities, without header and with the length headers which match the data. A
+    /// stream of another layout, or data which is not compressed, decodes to matches which
+    /// the encoder would not have written: matches referring to a position which it never had
+    /// in its window, matches followed by the byte after their source (greedy and lazy
+    /// encoders take the longest match found), and literals which repeat three bytes of the
+    /// window. The counts are kept in the [`Detection`], together with the end of the stream
+    /// and the entropy of the output.
+    ///
+    /// The score is a rough guess, short streams of a few matches can't be told apart. The fill
+    /// byte `C` is only detected if matches copy it, and guessed as the most frequent byte which
+    /// doesn't contradict the choices of the encoder.
+    ///
+    /// ```rust
+    /// # use lzs::{Dialect, Lzs, SliceReader, VecWriter};
+    /// let data: Vec<u8> = (0..200u32)
+    ///     .flat_map(|i| format!("line {}: {}\n", i, i * i % 97).into_bytes())
+    ///     .collect();
+    /// let input = Lzs::new(0x20)
+    ///     .compress_length_prefixed(SliceReader::new(&data), VecWriter::with_capacity(0))
+    ///     .unwrap();
+    ///
+    /// let detections = Dialect::detect(&input);
+    /// let best = &detections[0];
+    /// assert_eq!(best.dialect.position_bits(), 12);
+    /// assert!(best.dialect.literal_flag());
+    /// assert_eq!(best.dialect.header(), lzs::LengthHeader::Compressed);
+    /// assert_eq!(best.decompressed, data.len());
+    /// assert!(best.score > detections[1].score);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[must_use]
+    pub fn detect(data: &[u8]) -> Vec<Detection> {
+        let mut detections = Vec::new();
+        for position_bits in Self::POSITION_BITS {
+            for literal_flag in [true, false] {
+                let dialect = |header| Dialect {
+                    position_bits,
+                    literal_flag,
+                    header,
+                };
+                detections.push(Detection::new(dialect(LengthHeader::None), data, None));
+                let Some((header, stream)) = data.split_first_chunk::<4>() else {
+                    continue;
+                };
+                let declared = u32::from_le_bytes(*header) as usize;
+                if declared <= stream.len() && stream.len() - declared < PADDING {
+                    let stream = &stream[..declared];
+                    detections.push(Detection::new(
+                        dialect(LengthHeader::Compressed),
+                        stream,
+                        None,
+                    ));
+                }
+                let detection =
+                    Detection::new(dialect(LengthHeader::Decompressed), stream, Some(declared));
+                if detection.decompressed == declared {
+                    detections.push(detection);
+                }
+            }
+        }
+        detections.sort_by_key(|detection| core::cmp::Reverse(detection.score));
+        detections
+    }
+}
+
+/// The result of a [`Dialect`] on unknown data, see [`Dialect::detect`].
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Detection {
+    /// The tried layout.
+    pub dialect: Dialect,
+    /// The fill byte, if matches copy it.
+    pub c: Option<u8>,
+    /// How likely the layout is, from 0 to 100.
+    pub score: u32,
+    /// The number of decompressed bytes.
+    pub decompressed: usize,
+    /// The number of literals.
+    pub literals: usize,
+    /// The number of matches.
+    pub matches: usize,
+    /// The number of matches referring to a position which the encoder never had in its window.
+    pub invalid: usize,
+    /// The number of matches followed by the byte after their source.
+    pub extendable: usize,
+    /// The number of literals which repeat three bytes of the window.
+    pub repeated: usize,
+    /// The number of decompressed bytes copied from the initial fill, not from earlier copies.
+    pub prefill: usize,
+    /// The number of bytes after the last complete unit.
+    pub trailing: usize,
+    /// The entropy of the output in hundredths of bits per byte, approximately.
+    pub entropy: u32,
+}
+
+/// Decodes streams of any layout, the initial fill is not known.
+///
+/// The buffers are kept between the streams.
+pub(crate) struct Decoder {
+    dialect: Dialect,
+    buf: Vec<u16>,
+    pub(crate) output: Vec<u16>,
+    /// The output positions of the literals.
+    literals: Vec<usize>,
+    /// The number of times each byte would have extended a match of the fill.
+    excluded: [u32; 256],
+    /// The last output position of each hash of three bytes.
+    last: Vec<usize>,
+    pub(crate) matches: usize,
+    pub(crate) invalid: usize,
+    pub(crate) extendable: usize,
+    /// The number of bytes copied from positions which were never written.
+    pub(crate) prefill: usize,
+    /// The length of the complete units.
+    pub(crate) end: usize,
+}
+
+impl Decoder {
+    pub(crate) fn new(dialect: Dialect) -> Self {
+        Decoder {
+            dialect,
+            buf: vec![FILL; dialect.n()],
+            output: Vec::new(),
+            literals: Vec::new(),
+            excluded: [0; 256],
+            last: vec![usize::MAX; 1 << 16],
+            matches: 0,
+            invalid: 0,
+            extendable: 0,
+            prefill: 0,
+            end: 0,
+        }
+    }
+
+    /// Decode a stream, up to `limit` bytes if given.
+    ///
+    /// If `strict`, it stops before the flag byte of the first invalid match.
+    pub(crate) fn decode(&mut self, input: &[u8], limit: Option<usize>, strict: bool) {
+        let dialect = self.dialect;
+        let (n, f) = (dialect.n(), dialect.f());
+        let mask = n - 1;
+        // Only the written part of the buffer has to be filled again.
+        for i in 0..self.output.len().min(n) {
+            self.buf[(n - f + i) & mask] = FILL;
+        }
+        self.output.clear();
+        self.literals.clear();
+        self.excluded = [0; 256];
+        (
+            self.matches,
+            self.invalid,
+            self.extendable,
+            self.prefill,
+            self.end,
+        ) = (0, 0, 0, 0, 0);
+        let buf = &mut self.buf;
+        let output = &mut self.output;
+        // The position after the source of the last match, if it is shorter than F.
+        let mut next = None;
+        let mut read = 0;
+        let mut group = 0;
+        let mut flags = 0usize;
+
+        while limit.is_none_or(|limit| output.len() < limit) {
+            flags >>= 1;
+            if (flags & 256) == 0 {
+                let Some(&c) = input.get(read) else {
+                    break;
+                };
+                flags = c as usize | 0xFF00;
+                group = read;
+                read += 1;
+            }
+            let ring = (n - f + output.len()) & mask;
+            let (first, token) = if ((flags & 1) != 0) == dialect.literal_flag {
+                let Some(&c) = input.get(read) else {
+                    break;
+                };
+                read += 1;
+                (c as u16, None)
+            } else {
+                let (Some(&c1), Some(&c2)) = (input.get(read), input.get(read + 1)) else {
+                    break;
+                };
+                read += 2;
+                let pos = c1 as usize | ((c2 as usize >> dialect.length_bits()) << 8);
+                let len = (c2 as usize & ((1 << dialect.length_bits()) - 1)) + Lzs::threshold() + 1;
+                let distance = match (ring + n - pos) & mask {
+                    0 => n,
+                    distance => distance,
+                };
+                if distance > n - f {
+                    if strict {
+                        self.end = group;
+                        return;
+                    }
+                    self.invalid += 1;
+                }
+                self.matches += 1;
+                // The source starts `distance` bytes before, the initial fill before the output.
+                self.prefill += len.min(distance.saturating_sub(output.len()));
+                (buf[pos], Some((pos, len)))
+            };
+            if let Some(next) = next.take() {
+                match (buf[next], first) {
+                    (a, b) if a == b => self.extendable += 1,
+                    (FILL, c) | (c, FILL) => self.excluded[c as usize] += 1,
+                    _ => {}
+                }
+            }
+            if let Some((pos, len)) = token {
+                for i in 0..len {
+                    let c = buf[(pos + i) & mask];
+                    buf[(ring + i) & mask] = c;
+                    output.push(c);
+                }
+                if len < f {
+                    next = Some((pos + len) & mask);
+                }
+            } else {
+                buf[ring] = first;
+                self.literals.push(output.len());
+                output.push(first);
+            }
+            self.end = read;
+        }
+    }
+
+    /// Count the literals whose position starts three bytes which are in the window of the
+    /// encoder.
+    ///
+    /// Only the last occurrence of each hash is kept, some repetitions are missed.
+    fn repeated(&mut self) -> usize {
+        let output = &self.output;
+        let window = self.dialect.n() - self.dialect.f();
+        let hash = |i: usize| {
+            let key =
+                (output[i] as usize) << 18 | (output[i + 1] as usize) << 9 | output[i + 2] as usize;
+            (key.wrapping_mul(0x9E37_79B1) >> 16) & 0xFFFF
+        };
+        let mut literals = self.literals.iter().peekable();
+        let mut count = 0;
+        for i in 0..output.len().saturating_sub(2) {
+            let h = hash(i);
+            if literals.next_if_eq(&&i).is_some() {
+                let j = self.last[h];
+                if j != usize::MAX && i - j <= window && output[j..j + 3] == output[i..i + 3] {
+                    count += 1;
+                }
+            }
+            self.last[h] = i;
+        }
+        // Only the used hashes have to be cleared again.
+        for i in 0..output.len().saturating_sub(2) {
+            self.last[hash(i)] = usize::MAX;
+        }
+        count
+    }
+}
+
+impl Detection {
+    /// Decode the stream, up to `limit` bytes if given.
+    pub(crate) fn new(dialect: Dialect, input: &[u8], limit: Option<usize>) -> Self {
+        let mut decoder = Decoder::new(dialect);
+        decoder.decode(input, limit, false);
+        Self::of(&mut decoder, input.len())
+    }
+
+    /// Collect the result of a decoded stream.
+    pub(crate) fn of(decoder: &mut Decoder, len: usize) -> Self {
+        let window = decoder.dialect.n() - decoder.dialect.f();
+        let repeated = decoder.repeated();
+        let output = &decoder.output;
+        let mut detection = Detection {
+            dialect: decoder.dialect,
+            c: None,
+            score: 0,
+            decompressed: output.len(),
+            literals: decoder.literals.len(),
+            matches: decoder.matches,
+            invalid: decoder.invalid,
+            extendable: decoder.extendable,
+            repeated,
+            prefill: decoder.prefill,
+            trailing: len - decoder.end,
+            entropy: 0,
+        };
+        let mut counts = [0; 257];
+        for c in output {
+            counts[*c as usize] += 1;
+        }
+        if counts[FILL as usize] > 0 {
+            detection.c = guess_fill(output, &decoder.literals, window, decoder.excluded);
+            counts[detection.c.unwrap_or(0) as usize] += counts[FILL as usize];
+        }
+        detection.entropy = entropy(&counts[..256], output.len());
+        detection.score = detection.score();
+        detection
+    }
+
+    /// Follow the tokens of a stream until the first invalid match without decoding it.
+    ///
+    /// The score is an upper bound of the score of the decoded stream, since the counts which
+    /// need the output are zero. It rules out most offsets which don't start a stream much
+    /// faster than [`Decoder::decode`].
+    pub(crate) fn walk(dialect: Dialect, input: &[u8]) -> Self {
+        let (n, f) = (dialect.n(), dialect.f());
+        let mask = n - 1;
+        let mut detection = Detection {
+            dialect,
+            c: None,
+            score: 0,
+            decompressed: 0,
+            literals: 0,
+            matches: 0,
+            invalid: 0,
+            extendable: 0,
+            repeated: 0,
+            prefill: 0,
+            trailing: 0,
+            entropy: 0,
+        };
+        let mut read = 0;
+        let mut flags = 0usize;
+        loop {
+            flags >>= 1;
+            if (flags & 256) == 0 {
+                let Some(&c) = input.get(read) else {
+                    break;
+                };
+                flags = c as usize | 0xFF00;
+                read += 1;
+            }
+            if ((flags & 1) != 0) == dialect.literal_flag {
+                if read >= input.len() {
+                    break;
+                }
+                read += 1;
+                detection.literals += 1;
+                detection.decompressed += 1;
+            } else {
+                let (Some(&c1), Some(&c2)) = (input.get(read), input.get(read + 1)) else {
+                    break;
+                };
+                read += 2;
+                let pos = c1 as usize | ((c2 as usize >> dialect.length_bits()) << 8);
+                let len = (c2 as usize & ((1 << dialect.length_bits()) - 1)) + Lzs::threshold() + 1;
+                let ring = (n - f + detection.decompressed) & mask;
+                let distance = match (ring + n - pos) & mask {
+                    0 => n,
+                    distance => distance,
+                };
+                if distance > n - f {
+                    break;
+                }
+                detection.matches += 1;
+                detection.prefill += len.min(distance.saturating_sub(detection.decompressed));
+                detection.decompressed += len;
+            }
+        }
+        detection.score = detection.score();
+        detection
+    }
+
+    /// Combine the counts to a score.
+    fn score(&self) -> u32 {
+        self.rating() / 10
+    }
+
+    /// The score in tenths, which tells apart streams of almost the same score.
+    pub(crate) fn rating(&self) -> u32 {
+        if self.matches == 0 {
+            return 0;
+        }
+        let mut penalty = 0;
+        // No encoder writes invalid matches, they are the strongest hint.
+        if self.invalid > 0 {
+            penalty += (200 + self.invalid * 100_000 / self.matches).min(600);
+        }
+        // Extendable matches and repeated literals per mille, written by some encoders which
+        // don't search the whole window or don't always take the longest match. Optimal
+        // parsing shortens matches on purpose, so they count half.
+        let unlikely =
+            (self.extendable / 2 + self.repeated) * 1000 / (self.matches + self.literals);
+        penalty += unlikely.min(300);
+        // Matches of data which is not compressed copy mostly the fill, while the matches of a
+        // stream almost never do, unless the data starts with the fill byte.
+        penalty += (self.prefill * 10_000 / self.decompressed).min(400);
+        if self.trailing > 0 {
+            penalty += 150;
+        }
+        // Random bytes have about 8 bits per byte.
+        if self.entropy > 750 {
+            penalty += 100;
+        }
+        let rating = 1000u32.saturating_sub(penalty as u32);
+        // Fewer than 16 matches are weak evidence.
+        rating * self.matches.min(16) as u32 / 16
+    }
+
+    /// The parameters to decompress the stream with this crate, if the layout is supported.
+    ///
+    /// The fill byte is zero if matches don't copy it.
+    #[inline]
+    #[must_use]
+    pub fn lzs(&self) -> Option<Lzs> {
+        self.dialect
+            .is_supported()
+            .then(|| Lzs::new(self.c.unwrap_or(0)))
+    }
+}
+
+/// Guess the fill byte, which is copied by matches where the data repeats it.
+///
+/// It is the most frequent byte of the output, except the bytes which would have been
+/// matched with the fill: runs of three literals while it is in the window, and the bytes
+/// after matches whose source continues with it. Each of them quarters the count, a few
+/// bytes after the end of a stream without header may decode to anything.
+fn guess_fill(
+    output: &[u16],
+    literals: &[usize],
+    window: usize,
+    mut excluded: [u32; 256],
+) -> Option<u8> {
+    let mut counts = [0usize; 256];
+    for (i, run) in output.windows(3).enumerate() {
+        if run[0] != FILL {
+            counts[run[0] as usize] += 1;
+        }
+        if i + 3 < window && run[0] == run[1] && run[1] == run[2] && run[0] != FILL {
+            excluded[run[0] as usize] += u32::from(literals.binary_search(&i).is_ok());
+        }
+    }
+    let count =
+        |c: u8| counts[c as usize] >> excluded[c as usize].saturating_mul(2).min(usize::BITS - 1);
+    (0..=255u8)
+        .filter(|c| count(*c) > 0)
+        .max_by_key(|c| (count(*c), core::cmp::Reverse(*c)))
+}
+
+/// The entropy in hundredths of bits per byte, with an approximate logarithm.
+fn entropy(counts: &[usize], len: usize) -> u32 {
+    // log2 in 1/65536 bits, interpolated linearly between powers of two.
+    let log2 = |x: usize| {
+        let k = x.ilog2();
+        ((k as u64) << 16) + (((x as u64) << 16 >> k) - (1 << 16))
+    };
+    if len == 0 {
+        return 0;
+    }
+    let bits: u64 = counts
+        .iter()
+        .filter(|count| **count > 0)
+        .map(|count| *count as u64 * (log2(len) - log2(*count)))
+        .sum();
+    ((bits * 100 / len as u64) >> 16) as u32
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::detect::{Dialect, LengthHeader};
+    use crate::dynamic::{Lzs, Tokens};
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap()
+    }
+
+    #[test]
+    fn test_detect() {
+        let big_test_data = include_bytes!("dynamic/mod.rs");
+        let input = compress(big_test_data);
+        let detections = Dialect::detect(&input);
+        let best = detections[0];
+        assert_eq!(best.dialect, Dialect::LZS);
+        assert_eq!(best.decompressed, big_test_data.len());
+        assert_eq!((best.invalid, best.extendable, best.trailing), (0, 0, 0));
+        assert!(best.score >= 95);
+        assert!(detections[1].score < 70);
+        assert!(best.lzs().is_some());
+
+        // with a header of the decompressed length
+        let mut file = (big_test_data.len() as u32).to_le_bytes().to_vec();
+        file.extend_from_slice(&input);
+        let best = Dialect::detect(&file)[0];
+        assert_eq!(best.dialect.header(), LengthHeader::Decompressed);
+        assert_eq!(best.decompressed, big_test_data.len());
+    }
+
+    #[test]
+    fn test_detect_inverted_flags() {
+        let big_test_data = include_bytes!("dynamic/mod.rs");
+        let mut input = compress(big_test_data);
+        let mut end = 0;
+        for (offset, token) in Tokens::new(SliceReader::new(&compress(big_test_data))) {
+            if offset > end {
+                input[end] ^= 0xFF;
+            }
+            end = offset + token.compressed_len();
+        }
+        let best = Dialect::detect(&input)[0];
+        assert_eq!(best.dialect.position_bits(), 12);
+        assert!(!best.dialect.literal_flag());
+        assert_eq!(best.decompressed, big_test_data.len());
+        assert!(best.lzs().is_none());
+    }
+
+    #[test]
+    fn test_detect_fill() {
+        let data: Vec<u8> = (0..500)
+            .flat_map(|i| format!("    The square of {i} is {}.\n", i * i).into_bytes())
+            .collect();
+        let best = Dialect::detect(&compress(&data))[0];
+        assert_eq!(best.dialect, Dialect::LZS);
+        assert_eq!(best.c, Some(b' '));
+        assert_eq!(best.lzs().unwrap().decompress_vec(&compress(&data)), data);
+
+        // the fill is not copied
+        let input = Lzs::new(0xAA)
+            .compress(SliceReader::new(&data), VecWriter::with_capacity(0))
+            .void_unwrap();
+        let best = Dialect::detect(&input)[0];
+        assert_eq!(best.dialect, Dialect::LZS);
+        assert_eq!(best.c, None);
+    }
+}
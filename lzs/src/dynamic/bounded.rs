@@ -0,0 +1,74 @@
+use crate::dynamic::window::{compare, Finder};
+use crate::dynamic::Lzs;
+use crate::macros::get;
+
+/// Compares the current position with the previous `distance` positions, without any index.
+///
+/// Nothing has to be maintained, so positions within a match cost nothing.
+pub(crate) struct Bounded {
+    distance: usize,
+    /// Take the farthest of the longest matches instead of the nearest one.
+    farthest: bool,
+}
+
+impl Bounded {
+    #[inline(always)]
+    pub(crate) fn new(distance: usize, farthest: bool) -> Self {
+        Bounded {
+            distance: distance.clamp(1, Lzs::n() - Lzs::f()),
+            farthest,
+        }
+    }
+}
+
+impl Bounded {
+    /// Compare with the positions at the distances in order, only a longer match replaces the
+    /// first one found.
+    #[inline(always)]
+    fn search(
+        distances: impl Iterator<Item = usize>,
+        r: usize,
+        text_buf: &[u8; Lzs::n() + Lzs::f() - 1],
+    ) -> (usize, usize) {
+        let mut match_position = 0;
+        let mut match_length = 0;
+        let first = get!(text_buf, r);
+
+        for d in distances {
+            let p = (r + Lzs::n() - d) & (Lzs::n() - 1);
+            if get!(text_buf, p) != first {
+                continue;
+            }
+
+            let (i, _) = compare(text_buf, r, p);
+
+            if i > match_length {
+                match_position = p;
+                match_length = i;
+
+                if match_length >= Lzs::f() {
+                    break;
+                }
+            }
+        }
+
+        (match_position, match_length)
+    }
+}
+
+impl Finder for Bounded {
+    #[inline(always)]
+    fn insert(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) -> (usize, usize) {
+        if self.farthest {
+            Self::search((1..=self.distance).rev(), r, text_buf)
+        } else {
+            Self::search(1..=self.distance, r, text_buf)
+        }
+    }
+
+    #[inline(always)]
+    fn skip(&mut self, _r: usize, _text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) {}
+
+    #[inline(always)]
+    fn delete(&mut self, _p: usize) {}
+}
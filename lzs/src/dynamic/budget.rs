@@ -0,0 +1,149 @@
+use crate::dynamic::{Insertion, Lzs, MatchFinder, Parser};
+use crate::error::{BudgetExceeded, LzsError};
+use crate::read_write::Write;
+use crate::slice::{SliceReader, SliceWriter};
+use void::Void;
+
+/// Counts the written bytes.
+struct Counter(usize);
+
+impl Write for Counter {
+    type Output = usize;
+    type Error = Void;
+    #[inline(always)]
+    fn write(&mut self, _data: u8) -> Result<(), Self::Error> {
+        self.0 += 1;
+        Ok(())
+    }
+    #[inline(always)]
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok(self.0)
+    }
+}
+
+impl Lzs {
+    /// Compress the input into the output slice, with stronger strategies until it fits, e.g.
+    /// into the space of the original data on a disc.
+    ///
+    /// Returns the amount of written bytes. Starting with the [`Parser`] of `self`, the
+    /// parsers [`Greedy`](Parser::Greedy), [`Lazy`](Parser::Lazy) and
+    /// [`Optimal`](Parser::Optimal) are tried, each one first with the [`MatchFinder`] and
+    /// [`Insertion`] of `self`, which keep its choice between matches of the same length, e.g.
+    /// of a [`Compat`](crate::Compat) profile, then with [`MatchFinder::Tree`], which always
+    /// finds the longest match. Each try stops at the first byte which doesn't fit, nothing is
+    /// written past the output.
+    ///
+    /// If no strategy fits, the size of the smallest output is returned as error and the output
+    /// contains an incomplete try.
+    ///
+    /// ```rust
+    /// # use lzs::{BudgetExceeded, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let input = b"Example Data, Example Data, Example Data";
+    /// let greedy = Lzs::new(0x20)
+    ///     .compress(SliceReader::new(input), VecWriter::with_capacity(40))
+    ///     .void_unwrap();
+    /// let mut slot = [0; 30];
+    /// let len = Lzs::new(0x20).compress_within(input, &mut slot).unwrap();
+    /// assert_eq!(&slot[..len], greedy);
+    /// assert_eq!(Lzs::new(0x20).decompress_vec(&slot[..len]), input);
+    ///
+    /// let mut slot = [0; 10];
+    /// assert_eq!(
+    ///     Lzs::new(0x20).compress_within(input, &mut slot),
+    ///     Err(BudgetExceeded { required: 19, available: 10 })
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    pub fn compress_within(
+        &self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, BudgetExceeded> {
+        const PARSERS: [Parser; 3] = [Parser::Greedy, Parser::Lazy, Parser::Optimal];
+        let first = PARSERS.iter().position(|parser| *parser == self.parser);
+        let longest = Lzs {
+            finder: MatchFinder::Tree,
+            insertion: Insertion::All,
+            ..*self
+        };
+        let mut strongest = longest;
+        for parser in &PARSERS[first.unwrap_or(0)..] {
+            let own = self.with_parser(*parser);
+            strongest = longest.with_parser(*parser);
+            for lzs in [own, strongest] {
+                match lzs.compress(SliceReader::new(input), SliceWriter::new(output)) {
+                    Ok(len) => return Ok(len),
+                    Err(LzsError::WriteError(_)) => {}
+                    Err(LzsError::ReadError(void)) => void::unreachable(void),
+                }
+                if own == strongest {
+                    break;
+                }
+            }
+        }
+        let required = match strongest.compress(SliceReader::new(input), Counter(0)) {
+            Ok(required) => required,
+            Err(LzsError::ReadError(void) | LzsError::WriteError(void)) => void::unreachable(void),
+        };
+        Err(BudgetExceeded {
+            required,
+            available: output.len(),
+        })
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::{Lzs, Parser};
+    use crate::error::BudgetExceeded;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn compress(lzs: Lzs, data: &[u8]) -> Vec<u8> {
+        lzs.compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap()
+    }
+
+    #[test]
+    fn test_compress_within() {
+        let data = include_bytes!("mod.rs");
+        let greedy = compress(TEST_LZS, data);
+        let optimal = compress(TEST_LZS.with_parser(Parser::Optimal), data);
+        assert!(optimal.len() < greedy.len());
+        for len in [greedy.len(), optimal.len()] {
+            let mut output = vec![0; len];
+            let written = TEST_LZS.compress_within(data, &mut output).unwrap();
+            assert!(written <= len);
+            assert_eq!(TEST_LZS.decompress_vec(&output[..written]), data);
+        }
+        // the first fitting strategy is used, so a generous budget doesn't change the output
+        let mut output = vec![0; data.len()];
+        let written = TEST_LZS.compress_within(data, &mut output).unwrap();
+        assert_eq!(&output[..written], greedy);
+
+        let mut output = vec![0; optimal.len() - 1];
+        assert_eq!(
+            TEST_LZS.compress_within(data, &mut output),
+            Err(BudgetExceeded {
+                required: optimal.len(),
+                available: optimal.len() - 1
+            })
+        );
+        assert_eq!(TEST_LZS.compress_within(b"", &mut []), Ok(0));
+    }
+
+    #[test]
+    fn test_compress_within_level() {
+        // the weakest level falls back to the longest matches before a stronger parser
+        let data = include_bytes!("mod.rs");
+        let fast = TEST_LZS.with_level(0);
+        let tree = compress(TEST_LZS, data);
+        assert!(tree.len() < compress(fast, data).len());
+        let mut output = vec![0; tree.len()];
+        let written = fast.compress_within(data, &mut output).unwrap();
+        assert_eq!(&output[..written], tree);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::dynamic::Lzs;
+use crate::macros::{get, set};
+use crate::read_write::Write;
+
+/// Collects up to eight units with their flags, see `compress_internal`.
+pub(crate) struct CodeBuf {
+    /* buf[1..16] saves eight units of code, and
+     * buf[0] works as eight flags, "1" representing that the unit
+     * is an unencoded letter (1 byte), "0" a position-and-length pair
+     * (2 bytes).  Thus, eight units require at most 16 bytes of code.
+     */
+    buf: [u8; 17],
+    mask: u8,
+    ptr: usize,
+}
+
+impl CodeBuf {
+    #[inline(always)]
+    pub(crate) fn new() -> Self {
+        CodeBuf {
+            buf: [0u8; 17],
+            mask: 1,
+            ptr: 1,
+        }
+    }
+
+    /// Add an unencoded letter.
+    #[inline(always)]
+    pub(crate) fn literal<W: Write>(&mut self, c: u8, writer: &mut W) -> Result<(), W::Error> {
+        let buf = &mut self.buf;
+        set!(buf, 0, get!(buf, 0) | self.mask);
+        set!(buf, self.ptr, c);
+        self.ptr += 1;
+        self.next(writer)
+    }
+
+    /// Add a position-and-length pair, the length has to be in `THRESHOLD + 1..=F`.
+    #[inline(always)]
+    pub(crate) fn pair<W: Write>(
+        &mut self,
+        match_position: usize,
+        match_length: usize,
+        writer: &mut W,
+    ) -> Result<(), W::Error> {
+        let buf = &mut self.buf;
+        set!(buf, self.ptr, match_position as u8);
+        self.ptr += 1;
+        set!(
+            buf,
+            self.ptr,
+            (((match_position >> 4) & 0xF0) | (match_length - (Lzs::threshold() + 1))) as u8
+        );
+        self.ptr += 1;
+        self.next(writer)
+    }
+
+    #[inline(always)]
+    fn next<W: Write>(&mut self, writer: &mut W) -> Result<(), W::Error> {
+        self.mask <<= 1;
+
+        if self.mask == 0 {
+            for c in self.buf.iter().take(self.ptr) {
+                writer.write(*c)?;
+            }
+            self.buf[0] = 0;
+            self.mask = 1;
+            self.ptr = 1;
+        }
+        Ok(())
+    }
+
+    /// Send remaining code.
+    #[inline(always)]
+    pub(crate) fn finish<W: Write>(self, writer: &mut W) -> Result<(), W::Error> {
+        if self.ptr > 1 {
+            for c in self.buf.iter().take(self.ptr) {
+                writer.write(*c)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,275 +1,187 @@
 /* This file is based on the LZSS encoder-decoder  (c) Haruhiko Okumura */
 
-use crate::dynamic::Lzs;
+use crate::dynamic::bounded::Bounded;
+use crate::dynamic::code_buf::CodeBuf;
+use crate::dynamic::hash_chain::HashChain;
+use crate::dynamic::tree::Tree;
+use crate::dynamic::window::{Finder, Window};
+use crate::dynamic::{Insertion, Lzs, MatchFinder, Parser};
 use crate::error::LzsError;
-use crate::macros::{get, set};
 use crate::read_write::{Read, Write};
 
 impl Lzs {
-    // Allow many single char names, this is done to copy the original code as close as possible.
-    #![allow(clippy::many_single_char_names)]
     #[inline(always)]
     pub(crate) fn compress_internal<R: Read, W: Write>(
         self,
         reader: &mut R,
         writer: &mut W,
     ) -> Result<(), LzsError<R::Error, W::Error>> {
-        // Initialize the text_buf with C (a character that will appear often)
-        let mut text_buf = [self.c; Self::n() + Self::f() - 1];
-        // Initialize trees with N
-        let mut lson = [Self::n() as u16; Self::n() + 1];
-        let mut rson = [Self::n() as u16; Self::n() + 257];
-        let mut dad = [Self::n() as u16; Self::n() + 1];
-        /* code_buf[1..16] saves eight units of code, and
-         * code_buf[0] works as eight flags, "1" representing that the unit
-         * is an unencoded letter (1 byte), "0" a position-and-length pair
-         * (2 bytes).  Thus, eight units require at most 16 bytes of code.
-         */
-        let mut code_buf = [0u8; 17];
-        let mut mask = 1;
-        let mut code_buf_ptr = 1;
-        let mut s = 0;
-        let mut r = Self::n() - Self::f();
-        let mut len = 0;
+        self.compress_primed_by(0, reader, writer)
+    }
 
-        while len < Self::f() {
-            if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
-                set!(text_buf, r + len, c);
-            } else {
-                break;
-            }
+    /// Compress the input like [`compress_internal`](Lzs::compress_internal), but as if the
+    /// prefix was compressed (and decompressed) just before.
+    ///
+    /// The decoder has to be primed with the same prefix, see `Decoder::prime`.
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    pub(crate) fn compress_primed_internal<R: Read, W: Write>(
+        self,
+        prefix: &[u8],
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        let mut reader = PrefixReader { prefix, reader };
+        self.compress_primed_by(prefix.len(), &mut reader, writer)
+    }
 
-            len += 1;
+    #[inline(always)]
+    fn compress_primed_by<R: Read, W: Write>(
+        self,
+        primed: usize,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        match self.finder {
+            MatchFinder::Tree => self.compress_with(Tree::new(), primed, reader, writer),
+            MatchFinder::Bounded { distance } => self.compress_with(
+                Bounded::new(distance as usize, false),
+                primed,
+                reader,
+                writer,
+            ),
+            MatchFinder::Farthest { distance } => self.compress_with(
+                Bounded::new(distance as usize, true),
+                primed,
+                reader,
+                writer,
+            ),
+            MatchFinder::HashChain { depth } => {
+                self.compress_with(HashChain::new(depth as usize), primed, reader, writer)
+            }
         }
+    }
 
-        if len == 0 {
-            return Ok(());
+    #[inline(always)]
+    fn compress_with<F: Finder, R: Read, W: Write>(
+        self,
+        finder: F,
+        primed: usize,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        let every = match self.insertion {
+            Insertion::All => 1,
+            Insertion::Every(n) => n as usize,
+            Insertion::Skip => 0,
+        };
+        let window = Window::new(self.c, finder, every, primed);
+        match self.parser {
+            Parser::Greedy => Self::compress_greedy(window, reader, writer),
+            Parser::Lazy => Self::compress_lazy(window, reader, writer),
+            Parser::Optimal => Self::compress_optimal(window, reader, writer),
         }
+    }
 
-        for i in 1..=Self::f() {
-            Self::insert_node(r - i, &mut lson, &mut rson, &mut dad, &text_buf);
-        }
+    /// Always encode the longest match of the current position.
+    #[inline(always)]
+    fn compress_greedy<F: Finder, R: Read, W: Write>(
+        mut window: Window<F>,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        let mut code_buf = CodeBuf::new();
 
         let (mut match_position, mut match_length) =
-            Self::insert_node(r, &mut lson, &mut rson, &mut dad, &text_buf);
+            window.fill(reader).map_err(LzsError::ReadError)?;
 
-        loop {
-            if match_length > len {
-                match_length = len;
+        while window.len > 0 {
+            if match_length > window.len {
+                match_length = window.len;
             }
 
             if match_length <= Self::threshold() {
                 match_length = 1;
-                set!(code_buf, 0, get!(code_buf, 0) | mask);
-                set!(code_buf, code_buf_ptr, get!(text_buf, r));
-                code_buf_ptr += 1;
+                code_buf
+                    .literal(window.current(), writer)
+                    .map_err(LzsError::WriteError)?;
             } else {
-                set!(code_buf, code_buf_ptr, match_position as u8);
-                code_buf_ptr += 1;
-                set!(
-                    code_buf,
-                    code_buf_ptr,
-                    (((match_position >> 4) & 0xF0) | (match_length - (Self::threshold() + 1)))
-                        as u8
-                );
-                code_buf_ptr += 1;
-            }
-
-            mask <<= 1;
-
-            if mask == 0 {
-                for c in code_buf.iter().take(code_buf_ptr) {
-                    writer.write(*c).map_err(LzsError::WriteError)?;
-                }
-                set!(code_buf, 0, 0);
-                mask = 1;
-                code_buf_ptr = 1;
-            }
-
-            let last_match_length = match_length;
-            let mut i = 0;
-
-            while i < last_match_length {
-                if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
-                    Self::delete_node(s, &mut lson, &mut rson, &mut dad);
-                    set!(text_buf, s, c);
-
-                    if s < Self::f() - 1 {
-                        set!(text_buf, s + Self::n(), c);
-                    }
-
-                    s = (s + 1) & (Self::n() - 1);
-                    r = (r + 1) & (Self::n() - 1);
-
-                    (match_position, match_length) =
-                        Self::insert_node(r, &mut lson, &mut rson, &mut dad, &text_buf);
-                } else {
-                    break;
-                }
-
-                i += 1;
-            }
-
-            while i < last_match_length {
-                Self::delete_node(s, &mut lson, &mut rson, &mut dad);
-                s = (s + 1) & (Self::n() - 1);
-                r = (r + 1) & (Self::n() - 1);
-                len -= 1;
-                if len > 0 {
-                    (match_position, match_length) =
-                        Self::insert_node(r, &mut lson, &mut rson, &mut dad, &text_buf);
-                }
-                i += 1;
+                code_buf
+                    .pair(match_position, match_length, writer)
+                    .map_err(LzsError::WriteError)?;
             }
 
-            if len == 0 {
-                break;
-            }
-        }
-
-        if code_buf_ptr > 1 {
-            // Send remaining code
-            for c in code_buf.iter().take(code_buf_ptr) {
-                writer.write(*c).map_err(LzsError::WriteError)?;
-            }
+            window
+                .skip(reader, match_length - 1)
+                .map_err(LzsError::ReadError)?;
+            (match_position, match_length) = window.advance(reader).map_err(LzsError::ReadError)?;
         }
 
-        Ok(())
+        code_buf.finish(writer).map_err(LzsError::WriteError)
     }
 
-    /**
-     * Inserts string of length F, `text_buf[r..r+F-1]`, into one of the
-     * trees (`text_buf[r]`'th tree) and returns the longest-match position
-     * and length.
-     * If `match_length` = F, then removes the old node in favor of the new
-     * one, because the old one will be deleted sooner.
-     * Note r plays double role, as tree node and position in buffer.
-     */
+    /// Defer a match by one byte, encoding a literal instead, when the next position has a
+    /// longer match.
     #[inline(always)]
-    fn insert_node(
-        r: usize,
-        lson: &mut [u16; Self::n() + 1],
-        rson: &mut [u16; Self::n() + 257],
-        dad: &mut [u16; Self::n() + 1],
-        text_buf: &[u8; Self::n() + Self::f() - 1],
-    ) -> (usize, usize) {
-        let mut match_position = 0;
-        let mut match_length = 0;
-
-        let mut cmp = 1i32;
-        let mut p = Self::n() + 1 + get!(text_buf, r) as usize;
+    fn compress_lazy<F: Finder, R: Read, W: Write>(
+        mut window: Window<F>,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        let mut code_buf = CodeBuf::new();
 
-        set!(lson, r, Self::n() as u16);
-        set!(rson, r, Self::n() as u16);
+        let (mut match_position, mut match_length) =
+            window.fill(reader).map_err(LzsError::ReadError)?;
 
-        loop {
-            if cmp >= 0 {
-                if get!(rson, p) == Self::n() as u16 {
-                    set!(rson, p, r as u16);
-                    set!(dad, r, p as u16);
-                    return (match_position, match_length);
-                }
-                p = get!(rson, p) as usize;
-            } else if get!(lson, p) == Self::n() as u16 {
-                set!(lson, p, r as u16);
-                set!(dad, r, p as u16);
-                return (match_position, match_length);
-            } else {
-                p = get!(lson, p) as usize;
+        while window.len > 0 {
+            if match_length > window.len {
+                match_length = window.len;
             }
 
-            let mut i = 1;
-            while i < Self::f() {
-                cmp = get!(text_buf, r + i) as i32 - get!(text_buf, p + i) as i32;
-                if cmp != 0 {
-                    break;
-                }
-                i += 1;
+            let c = window.current();
+            let (next_position, mut next_length) =
+                window.advance(reader).map_err(LzsError::ReadError)?;
+            if next_length > window.len {
+                next_length = window.len;
             }
 
-            if i > match_length {
-                match_position = p;
-                match_length = i;
-
-                if match_length >= Self::f() {
-                    break;
-                }
+            if match_length <= Self::threshold() || next_length > match_length {
+                code_buf.literal(c, writer).map_err(LzsError::WriteError)?;
+                (match_position, match_length) = (next_position, next_length);
+            } else {
+                // The match was found before advancing, the decoder still sees the same window.
+                code_buf
+                    .pair(match_position, match_length, writer)
+                    .map_err(LzsError::WriteError)?;
+                window
+                    .skip(reader, match_length - 2)
+                    .map_err(LzsError::ReadError)?;
+                (match_position, match_length) =
+                    window.advance(reader).map_err(LzsError::ReadError)?;
             }
         }
 
-        set!(dad, r, get!(dad, p));
-        set!(lson, r, get!(lson, p));
-        set!(rson, r, get!(rson, p));
-
-        let e = get!(lson, p) as usize;
-        set!(dad, e, r as u16);
-        let e = get!(rson, p) as usize;
-        set!(dad, e, r as u16);
-
-        let e = get!(dad, p) as usize;
-        if get!(rson, e) == p as u16 {
-            set!(rson, e, r as u16);
-        } else {
-            set!(lson, e, r as u16);
-        }
-
-        set!(dad, p, Self::n() as u16); // Remove p
-
-        (match_position, match_length)
+        code_buf.finish(writer).map_err(LzsError::WriteError)
     }
+}
 
-    /**
-     * deletes node p from tree
-     */
-    #[inline(always)]
-    fn delete_node(
-        p: usize,
-        lson: &mut [u16; Self::n() + 1],
-        rson: &mut [u16; Self::n() + 257],
-        dad: &mut [u16; Self::n() + 1],
-    ) {
-        if get!(dad, p) == Self::n() as u16 {
-            return; // Not in tree
-        }
-
-        let q = if get!(rson, p) == Self::n() as u16 {
-            get!(lson, p) as usize
-        } else if get!(lson, p) == Self::n() as u16 {
-            get!(rson, p) as usize
-        } else {
-            let mut q = get!(lson, p) as usize;
-            if get!(rson, q) != Self::n() as u16 {
-                loop {
-                    q = get!(rson, q) as usize;
-
-                    if get!(rson, q) == Self::n() as u16 {
-                        break;
-                    }
-                }
-                let e = get!(dad, q) as usize;
-                set!(rson, e, get!(lson, q));
-                let e = get!(lson, q) as usize;
-                set!(dad, e, get!(dad, q));
-                set!(lson, q, get!(lson, p));
-                let e = get!(lson, p) as usize;
-                set!(dad, e, q as u16);
-            }
-            set!(rson, q, get!(rson, p));
-            let e = get!(rson, p) as usize;
-            set!(dad, e, q as u16);
-            q
-        };
-
-        let e = get!(dad, p);
-        set!(dad, q, e);
+/// Reads the prefix before the reader.
+#[cfg(feature = "alloc")]
+struct PrefixReader<'a, R> {
+    prefix: &'a [u8],
+    reader: &'a mut R,
+}
 
-        let e = get!(dad, p) as usize;
-        if get!(rson, e) == p as u16 {
-            set!(rson, e, q as u16);
+#[cfg(feature = "alloc")]
+impl<R: Read> Read for PrefixReader<'_, R> {
+    type Error = R::Error;
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<u8>, Self::Error> {
+        if let Some((c, rest)) = self.prefix.split_first() {
+            self.prefix = rest;
+            Ok(Some(*c))
         } else {
-            set!(lson, e, q as u16);
+            self.reader.read()
         }
-
-        set!(dad, p, Self::n() as u16);
     }
 }
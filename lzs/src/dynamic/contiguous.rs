@@ -0,0 +1,210 @@
+use crate::dynamic::Lzs;
+use crate::macros::{get, set};
+use crate::slice::SliceWriteError;
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+
+/// An output which is also used as the window of the decoder.
+trait Contiguous {
+    /// Make sure `len` bytes can be written at `pos`, and return the whole output.
+    fn reserve(&mut self, pos: usize, len: usize) -> Result<&mut [u8], SliceWriteError>;
+}
+
+impl Contiguous for &mut [u8] {
+    #[inline(always)]
+    fn reserve(&mut self, pos: usize, len: usize) -> Result<&mut [u8], SliceWriteError> {
+        if pos + len > self.len() {
+            return Err(SliceWriteError);
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Contiguous for Vec<u8> {
+    #[inline(always)]
+    fn reserve(&mut self, pos: usize, len: usize) -> Result<&mut [u8], SliceWriteError> {
+        if pos + len > self.len() {
+            self.resize((pos + len).max(2 * self.len()), 0);
+        }
+        Ok(self)
+    }
+}
+
+impl Lzs {
+    /// Decompress the input data into the output, which has to be large enough.
+    ///
+    /// Returns the amount of written bytes, like [`SliceWriter`](crate::SliceWriter). The
+    /// result is the same as [`decompress`](Lzs::decompress), but the written output is used as
+    /// the buffer and matches are copied at once, which is faster. Nothing is allocated.
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let input = Lzs::new(0x20)
+    ///     .compress(SliceReader::new(b"Example Data, Example Data"), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// let mut output = [0; 30];
+    /// let len = Lzs::new(0x20).decompress_slice(&input, &mut output).unwrap();
+    /// assert_eq!(&output[..len], b"Example Data, Example Data");
+    /// ```
+    pub fn decompress_slice(
+        &self,
+        input: &[u8],
+        mut output: &mut [u8],
+    ) -> Result<usize, SliceWriteError> {
+        self.decompress_contiguous(input, &mut output)
+    }
+
+    /// Decompress the input data into a vector.
+    ///
+    /// Like [`decompress_slice`](Lzs::decompress_slice), but the vector grows as needed.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn decompress_vec(&self, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        let len = self
+            .decompress_contiguous(input, &mut output)
+            .expect("the vector grows");
+        output.truncate(len);
+        output
+    }
+
+    #[inline(always)]
+    fn decompress_contiguous<O: Contiguous>(
+        self,
+        input: &[u8],
+        output: &mut O,
+    ) -> Result<usize, SliceWriteError> {
+        // The amount of written bytes, the buffer of the decoder starts at N - F.
+        let mut written = 0;
+        let mut read = 0;
+
+        'flags: while read < input.len() {
+            let mut flags = get!(input, read);
+            read += 1;
+
+            for _ in 0..8 {
+                if (flags & 1) != 0 {
+                    if read >= input.len() {
+                        break 'flags;
+                    }
+                    let buf = output.reserve(written, 1)?;
+                    set!(buf, written, get!(input, read));
+                    read += 1;
+                    written += 1;
+                } else {
+                    if read + 1 >= input.len() {
+                        break 'flags;
+                    }
+                    let (c1, c2) = (get!(input, read) as usize, get!(input, read + 1) as usize);
+                    read += 2;
+                    let pos = c1 | ((c2 & 0xF0) << 4);
+                    let len = (c2 & 0x0F) + Self::threshold() + 1;
+                    let buf = output.reserve(written, len)?;
+                    self.copy_match(buf, written, pos, len);
+                    written += len;
+                }
+                flags >>= 1;
+            }
+        }
+
+        Ok(written)
+    }
+
+    /// Copy the match at `pos` in the buffer of the decoder to `written` in the output.
+    #[inline(always)]
+    pub(crate) fn copy_match(self, buf: &mut [u8], written: usize, pos: usize, len: usize) {
+        // The distance in the output, the current position refers to N bytes before.
+        let ring = (Self::n() - Self::f() + written) & (Self::n() - 1);
+        let distance = match (ring + Self::n() - pos) & (Self::n() - 1) {
+            0 => Self::n(),
+            distance => distance,
+        };
+
+        if distance > written {
+            // The initial buffer is filled with C.
+            for j in written..written + len {
+                let c = if j >= distance {
+                    get!(buf, j - distance)
+                } else {
+                    self.c
+                };
+                set!(buf, j, c);
+            }
+        } else if distance >= len {
+            buf.copy_within(written - distance..written - distance + len, written);
+        } else if distance == 1 {
+            let c = get!(buf, written - 1);
+            buf[written..written + len].fill(c);
+        } else {
+            // Repeat the last distance bytes.
+            let mut start = written;
+            while start < written + len {
+                let chunk = distance.min(written + len - start);
+                buf.copy_within(start - distance..start - distance + chunk, start);
+                start += chunk;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::slice::{SliceReader, SliceWriteError};
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn decompress(input: &[u8]) -> Vec<u8> {
+        TEST_LZS
+            .decompress(SliceReader::new(input), VecWriter::with_capacity(0))
+            .void_unwrap()
+    }
+
+    #[test]
+    fn test_decompress_vec() {
+        let big_test_data = include_bytes!("mod.rs");
+        let compressed = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert_eq!(TEST_LZS.decompress_vec(&compressed), big_test_data);
+        // also truncated streams
+        for len in 0..100 {
+            let input = &compressed[..len];
+            assert_eq!(TEST_LZS.decompress_vec(input), decompress(input));
+        }
+    }
+
+    #[test]
+    fn test_decompress_arbitrary() {
+        // any distance, overlap and reference to the initial buffer
+        let mut state = 0x1234_5678u32;
+        let input: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect();
+        let expected = decompress(&input);
+        assert_eq!(TEST_LZS.decompress_vec(&input), expected);
+
+        let mut output = vec![0; expected.len()];
+        assert_eq!(
+            TEST_LZS.decompress_slice(&input, &mut output),
+            Ok(expected.len())
+        );
+        assert_eq!(output, expected);
+        assert_eq!(
+            TEST_LZS.decompress_slice(&input, &mut output[..expected.len() - 1]),
+            Err(SliceWriteError)
+        );
+    }
+}
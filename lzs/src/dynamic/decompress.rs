@@ -1,57 +1,90 @@
 /* This file is based on the LZSS encoder-decoder  (c) Haruhiko Okumura */
 
+use crate::dynamic::token::{read_token, Token};
 use crate::dynamic::Lzs;
 use crate::error::LzsError;
 use crate::macros::{get, set};
 use crate::read_write::{Read, Write};
 
-impl Lzs {
-    // Allow many single char names, this is done to copy the original code as close as possible.
-    #[allow(clippy::many_single_char_names)]
+/// The state of the decoder between two units.
+#[derive(Clone)]
+pub(crate) struct Decoder {
+    /// The ring buffer.
+    pub(crate) buffer: [u8; Lzs::n()],
+    /// The current position in the ring buffer.
+    pub(crate) r: usize,
+    /// The remaining flags, the high byte is used to count eight.
+    pub(crate) flags: usize,
+}
+
+impl Decoder {
     #[inline(always)]
-    pub(crate) fn decompress_internal<R: Read, W: Write>(
-        self,
+    pub(crate) fn new(c: u8) -> Self {
+        Decoder {
+            buffer: [c; Lzs::n()],
+            r: Lzs::n() - Lzs::f(),
+            flags: 0,
+        }
+    }
+
+    /// Add already decoded bytes to the ring buffer, as if they were written.
+    #[inline(always)]
+    pub(crate) fn prime(&mut self, prefix: &[u8]) {
+        let buffer = &mut self.buffer;
+        for c in prefix {
+            set!(buffer, self.r, *c);
+            self.r = (self.r + 1) & (Lzs::n() - 1);
+        }
+    }
+
+    /// Decode one unit (a literal or a position-and-length pair).
+    ///
+    /// Returns `false` when the input is exhausted.
+    #[inline(always)]
+    pub(crate) fn step<R: Read, W: Write>(
+        &mut self,
         reader: &mut R,
         writer: &mut W,
-    ) -> Result<(), LzsError<R::Error, W::Error>> {
-        let mut buffer = [self.c; Self::n()];
-        let mut r = Self::n() - Self::f();
-        let mut flags: usize = 0;
-
-        loop {
-            flags >>= 1;
-
-            if (flags & 256) == 0 {
-                if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
-                    flags = c as usize | 0xFF00;
-                } else {
-                    return Ok(());
-                }
-            }
+    ) -> Result<bool, LzsError<R::Error, W::Error>> {
+        let buffer = &mut self.buffer;
+        let mut r = self.r;
 
-            if (flags & 1) != 0 {
-                if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
-                    writer.write(c).map_err(LzsError::WriteError)?;
-                    set!(buffer, r, c);
-                    r = (r + 1) & (Self::n() - 1);
-                } else {
-                    return Ok(());
-                }
-            } else if let (Some(c1), Some(c2)) = (
-                reader.read().map_err(LzsError::ReadError)?,
-                reader.read().map_err(LzsError::ReadError)?,
-            ) {
-                let i = c1 as usize | ((c2 as usize & 0xF0) << 4);
-                let j = (c2 as usize & 0x0F) + Self::threshold();
-                for k in 0..=j {
-                    let c = get!(buffer, (i + k) & (Self::n() - 1));
+        match read_token(reader, &mut self.flags).map_err(LzsError::ReadError)? {
+            Some(Token::Literal(c)) => {
+                writer.write(c).map_err(LzsError::WriteError)?;
+                set!(buffer, r, c);
+                r = (r + 1) & (Lzs::n() - 1);
+            }
+            Some(Token::Match { pos, len }) => {
+                for k in 0..len {
+                    let c = get!(buffer, (pos + k) & (Lzs::n() - 1));
                     writer.write(c).map_err(LzsError::WriteError)?;
                     set!(buffer, r, c);
-                    r = (r + 1) & (Self::n() - 1);
+                    r = (r + 1) & (Lzs::n() - 1);
                 }
-            } else {
-                return Ok(());
             }
+            None => return Ok(false),
         }
+        self.r = r;
+
+        Ok(true)
+    }
+}
+
+impl Lzs {
+    #[inline(always)]
+    pub(crate) fn decoder(self) -> Decoder {
+        Decoder::new(self.c)
+    }
+
+    #[inline(always)]
+    pub(crate) fn decompress_internal<R: Read, W: Write>(
+        self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        let mut decoder = self.decoder();
+        while decoder.step(reader, writer)? {}
+        Ok(())
     }
 }
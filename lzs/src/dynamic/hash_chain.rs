@@ -0,0 +1,90 @@
+use crate::dynamic::window::{compare, Finder};
+use crate::dynamic::Lzs;
+use crate::macros::{get, set};
+
+/// The number of bits of the hash of the first `THRESHOLD + 1` bytes.
+const HASH_BITS: usize = 11;
+
+/// Lists the previous positions starting with the same bytes, from the nearest one.
+///
+/// Positions are not removed, instead a chain ends when the distance does not grow anymore,
+/// i.e. when a position was overwritten.
+pub(crate) struct HashChain {
+    depth: usize,
+    head: [u16; 1 << HASH_BITS],
+    prev: [u16; Lzs::n()],
+}
+
+impl HashChain {
+    #[inline(always)]
+    pub(crate) fn new(depth: usize) -> Self {
+        HashChain {
+            depth: depth.max(1),
+            head: [Lzs::n() as u16; 1 << HASH_BITS],
+            prev: [Lzs::n() as u16; Lzs::n()],
+        }
+    }
+
+    #[inline(always)]
+    fn hash(r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) -> usize {
+        let key = (get!(text_buf, r) as u32) << 16
+            | (get!(text_buf, r + 1) as u32) << 8
+            | get!(text_buf, r + 2) as u32;
+        (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+    }
+}
+
+impl Finder for HashChain {
+    #[inline(always)]
+    fn insert(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) -> (usize, usize) {
+        let HashChain { head, prev, .. } = self;
+        let mut match_position = 0;
+        let mut match_length = 0;
+        let h = Self::hash(r, text_buf);
+
+        let mut p = get!(head, h) as usize;
+        let mut last_distance = 0;
+        for _ in 0..self.depth {
+            if p == Lzs::n() {
+                break;
+            }
+            let distance = (r + Lzs::n() - p) & (Lzs::n() - 1);
+            if distance <= last_distance || distance > Lzs::n() - Lzs::f() {
+                break;
+            }
+            last_distance = distance;
+
+            if get!(text_buf, r) != get!(text_buf, p) {
+                p = get!(prev, p) as usize;
+                continue;
+            }
+            let (i, _) = compare(text_buf, r, p);
+
+            if i > match_length {
+                match_position = p;
+                match_length = i;
+
+                if match_length >= Lzs::f() {
+                    break;
+                }
+            }
+            p = get!(prev, p) as usize;
+        }
+
+        set!(prev, r, get!(head, h));
+        set!(head, h, r as u16);
+
+        (match_position, match_length)
+    }
+
+    #[inline(always)]
+    fn skip(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) {
+        let HashChain { head, prev, .. } = self;
+        let h = Self::hash(r, text_buf);
+        set!(prev, r, get!(head, h));
+        set!(head, h, r as u16);
+    }
+
+    #[inline(always)]
+    fn delete(&mut self, _p: usize) {}
+}
@@ -0,0 +1,188 @@
+use crate::dynamic::Lzs;
+use crate::error::InPlaceError;
+use crate::macros::{get, set};
+
+impl Lzs {
+    /// The amount of bytes the buffer of [`decompress_in_place`](Lzs::decompress_in_place) needs
+    /// in addition to the decompressed data.
+    ///
+    /// With this margin the output never overwrites input which is not read yet. Only the input
+    /// is scanned, nothing is decompressed.
+    #[must_use]
+    pub fn in_place_margin(&self, input: &[u8]) -> usize {
+        let (len, ahead) = Self::scan_in_place(input);
+        input.len() + ahead - len
+    }
+
+    /// Returns the length of the decompressed data and the maximal amount of bytes by which the
+    /// output is ahead of the input, after each unit.
+    fn scan_in_place(input: &[u8]) -> (usize, usize) {
+        let mut written = 0;
+        let mut read = 0;
+        let mut ahead = 0;
+
+        'flags: while read < input.len() {
+            let mut flags = get!(input, read);
+            read += 1;
+
+            for _ in 0..8 {
+                if (flags & 1) != 0 {
+                    if read >= input.len() {
+                        break 'flags;
+                    }
+                    read += 1;
+                    written += 1;
+                } else {
+                    if read + 1 >= input.len() {
+                        break 'flags;
+                    }
+                    written += (get!(input, read + 1) as usize & 0x0F) + Self::threshold() + 1;
+                    read += 2;
+                }
+                ahead = ahead.max(written.saturating_sub(read));
+                flags >>= 1;
+            }
+        }
+
+        (written, ahead)
+    }
+
+    /// Decompress the last `input_len` bytes of the buffer into its start.
+    ///
+    /// Returns the amount of written bytes. The buffer has to be at least
+    /// [`in_place_margin`](Lzs::in_place_margin) bytes longer than the decompressed data,
+    /// otherwise nothing is written and the required length is returned as error. The result
+    /// is the same as [`decompress_slice`](Lzs::decompress_slice).
+    ///
+    /// Panics if `input_len` exceeds the buffer.
+    ///
+    /// ```rust
+    /// # use lzs::{InPlaceError, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let lzs = Lzs::new(0x20);
+    /// let input = lzs
+    ///     .compress(SliceReader::new(b"Example Data, Example Data"), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// let margin = lzs.in_place_margin(&input);
+    ///
+    /// let mut buffer = vec![0; 26 + margin];
+    /// let start = buffer.len() - input.len();
+    /// buffer[start..].copy_from_slice(&input);
+    /// assert_eq!(lzs.decompress_in_place(&mut buffer, input.len()), Ok(26));
+    /// assert_eq!(&buffer[..26], b"Example Data, Example Data");
+    ///
+    /// let mut buffer = vec![0; 25 + margin];
+    /// let start = buffer.len() - input.len();
+    /// buffer[start..].copy_from_slice(&input);
+    /// assert_eq!(
+    ///     lzs.decompress_in_place(&mut buffer, input.len()),
+    ///     Err(InPlaceError { required: 26 + margin, available: 25 + margin })
+    /// );
+    /// ```
+    pub fn decompress_in_place(
+        &self,
+        buffer: &mut [u8],
+        input_len: usize,
+    ) -> Result<usize, InPlaceError> {
+        assert!(input_len <= buffer.len(), "input exceeds the buffer");
+        let end = buffer.len();
+        let (len, ahead) = Self::scan_in_place(&buffer[end - input_len..]);
+        if end < input_len + ahead {
+            return Err(InPlaceError {
+                required: input_len + ahead,
+                available: end,
+            });
+        }
+        debug_assert!(len <= end);
+
+        let mut written = 0;
+        let mut read = end - input_len;
+
+        'flags: while read < end {
+            let mut flags = get!(buffer, read);
+            read += 1;
+
+            for _ in 0..8 {
+                if (flags & 1) != 0 {
+                    if read >= end {
+                        break 'flags;
+                    }
+                    set!(buffer, written, get!(buffer, read));
+                    read += 1;
+                    written += 1;
+                } else {
+                    if read + 1 >= end {
+                        break 'flags;
+                    }
+                    let (c1, c2) = (get!(buffer, read) as usize, get!(buffer, read + 1) as usize);
+                    read += 2;
+                    let pos = c1 | ((c2 & 0xF0) << 4);
+                    let len = (c2 & 0x0F) + Self::threshold() + 1;
+                    self.copy_match(buffer, written, pos, len);
+                    written += len;
+                }
+                flags >>= 1;
+            }
+        }
+
+        Ok(written)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::error::InPlaceError;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn in_place(input: &[u8], len: usize) -> (Result<usize, InPlaceError>, Vec<u8>) {
+        let mut buffer = vec![0; len.max(input.len())];
+        let start = buffer.len() - input.len();
+        buffer[start..].copy_from_slice(input);
+        let result = TEST_LZS.decompress_in_place(&mut buffer, input.len());
+        (result, buffer)
+    }
+
+    #[test]
+    fn test_decompress_in_place() {
+        let big_test_data = include_bytes!("mod.rs");
+        for data in [&big_test_data[..], &[0; 5000], b"", b"a"] {
+            let compressed = TEST_LZS
+                .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+                .void_unwrap();
+            let margin = TEST_LZS.in_place_margin(&compressed);
+            let (result, buffer) = in_place(&compressed, data.len() + margin);
+            assert_eq!(result, Ok(data.len()));
+            assert_eq!(&buffer[..data.len()], data);
+            if data.len() + margin > compressed.len() {
+                let (result, _) = in_place(&compressed, data.len() + margin - 1);
+                assert_eq!(
+                    result,
+                    Err(InPlaceError {
+                        required: data.len() + margin,
+                        available: data.len() + margin - 1
+                    })
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_in_place_margin() {
+        // literals need the whole input in the buffer, with a flag byte per eight, but long
+        // matches do not need any margin
+        let literals: Vec<u8> = (0..=255).collect();
+        let compressed = TEST_LZS
+            .compress(SliceReader::new(&literals), VecWriter::with_capacity(300))
+            .void_unwrap();
+        assert_eq!(TEST_LZS.in_place_margin(&compressed), 32);
+        let compressed = TEST_LZS
+            .compress(SliceReader::new(&[0; 1000]), VecWriter::with_capacity(300))
+            .void_unwrap();
+        assert_eq!(TEST_LZS.in_place_margin(&compressed), 0);
+        assert_eq!(TEST_LZS.in_place_margin(&[]), 0);
+    }
+}
@@ -1,8 +1,116 @@
 use crate::error::LzsError;
 use crate::read_write::{Read, Write};
 
+mod bounded;
+#[cfg(feature = "alloc")]
+mod budget;
+mod code_buf;
 mod compress;
+mod contiguous;
 mod decompress;
+mod hash_chain;
+mod in_place;
+mod optimal;
+#[cfg(feature = "alloc")]
+mod recompress;
+#[cfg(feature = "alloc")]
+mod recover;
+mod small;
+mod token;
+mod tree;
+mod window;
+
+pub(crate) use decompress::Decoder;
+#[cfg(feature = "alloc")]
+pub use recover::{Recovered, RecoveredRange};
+pub use token::{Token, TokenWriter, Tokens};
+
+/// The strategy used to choose between literals and matches while compressing.
+///
+/// All strategies produce streams which are decoded the same way.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Parser {
+    /// Always take the longest match of the current position (the original behaviour).
+    #[default]
+    Greedy,
+    /// Emit a literal instead of a match if the next position has a longer match.
+    ///
+    /// Usually a bit smaller at a similar speed.
+    Lazy,
+    /// Choose the literals and matches with the smallest output.
+    ///
+    /// The input is buffered in chunks of 4096 bytes, which take 28 KiB on the stack, and
+    /// compression is slower. Intended for data compressed once and decompressed many times.
+    Optimal,
+}
+
+/// The way matches are searched in the window while compressing.
+///
+/// All match finders produce streams which are decoded the same way.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum MatchFinder {
+    /// Binary search trees over all positions of the window (the original behaviour).
+    ///
+    /// Always finds the longest match, but the trees are updated for every input byte.
+    #[default]
+    Tree,
+    /// Compare with the previous `distance` positions (at most `N - F`), without any index.
+    ///
+    /// Faster for small distances, since the positions within a match are skipped, but the
+    /// matches are shorter.
+    Bounded {
+        /// The number of positions compared.
+        distance: u16,
+    },
+    /// Like [`Bounded`](MatchFinder::Bounded), but the farthest of the longest matches is taken
+    /// instead of the nearest one.
+    Farthest {
+        /// The number of positions compared.
+        distance: u16,
+    },
+    /// Compare with the previous positions which start with the same bytes, from the nearest
+    /// one, following at most `depth` of them.
+    ///
+    /// Faster than [`Tree`](MatchFinder::Tree) on large inputs with few repetitions, but may
+    /// miss the longest match. Its tables take 12 KiB whatever the depth (24 KiB for the
+    /// trees), the depth only bounds the time spent at each position.
+    HashChain {
+        /// The number of positions compared.
+        depth: u16,
+    },
+}
+
+/// Which positions within a match are added to the [`MatchFinder`] while compressing.
+///
+/// Positions which are not added can't be found as matches later, but adding them takes most of
+/// the time on very repetitive data. Ignored by [`Parser::Optimal`], which adds all positions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Insertion {
+    /// Add all positions (the original behaviour).
+    #[default]
+    All,
+    /// Add every `n`th position within a match (`Every(0)` adds none).
+    Every(u8),
+    /// Add no position within a match.
+    Skip,
+}
+
+/// Profiles reproducing the output of known encoders, which differ in the choice between
+/// matches of the same length.
+///
+/// Each profile sets the [`MatchFinder`], the [`Insertion`] and the [`Parser`], see
+/// [`Lzs::with_compat`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Compat {
+    /// `LZSS.C` (1989) by Haruhiko Okumura, whose binary trees take the first match found
+    /// along the tree and replace older positions on matches of the full length (the default).
+    #[default]
+    Okumura,
+    /// The later brute-force `lzss.c` by Haruhiko Okumura, with `EI = 12`, `EJ = 4` and `P = 2`
+    /// for this format, which searches all `N - F` previous positions from the current one and
+    /// takes the nearest of the longest matches.
+    Nearest,
+}
 
 /// Dynamic parameters for de-/compression (see [Lzs](crate::Lzs) for compile-time parameters).
 ///
@@ -23,6 +131,9 @@ mod decompress;
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Lzs {
     c: u8,
+    finder: MatchFinder,
+    insertion: Insertion,
+    parser: Parser,
 }
 
 impl Lzs {
@@ -33,7 +144,173 @@ impl Lzs {
     /// For creating a const see [`Lzs::as_dyn`](crate::generic::Lzs::as_dyn).
     #[must_use]
     pub const fn new(c: u8) -> Self {
-        Lzs { c }
+        Lzs {
+            c,
+            finder: MatchFinder::Tree,
+            insertion: Insertion::All,
+            parser: Parser::Greedy,
+        }
+    }
+
+    /// The compression level of [`Lzs::new`].
+    pub const DEFAULT_LEVEL: u8 = 6;
+
+    /// Select the [`MatchFinder`], [`Insertion`] and [`Parser`] by a compression level from 0 (fastest) to 9
+    /// (smallest).
+    ///
+    /// | Level | Match finder                              | Parser  |
+    /// |-------|-------------------------------------------|---------|
+    /// | 0     | [`HashChain`](MatchFinder::HashChain) 1, [`Insertion::Skip`] | Greedy |
+    /// | 1     | [`HashChain`](MatchFinder::HashChain) 4   | Greedy  |
+    /// | 2     | [`HashChain`](MatchFinder::HashChain) 8   | Greedy  |
+    /// | 3     | [`HashChain`](MatchFinder::HashChain) 16  | Greedy  |
+    /// | 4     | [`HashChain`](MatchFinder::HashChain) 64  | Greedy  |
+    /// | 5     | [`HashChain`](MatchFinder::HashChain) 256 | Greedy  |
+    /// | 6     | [`Tree`](MatchFinder::Tree)               | Greedy  |
+    /// | 7     | [`HashChain`](MatchFinder::HashChain) 256 | Lazy    |
+    /// | 8     | [`Tree`](MatchFinder::Tree)               | Lazy    |
+    /// | 9     | [`Tree`](MatchFinder::Tree)               | Optimal |
+    ///
+    /// The default level is 6, its output does not change. The other levels may be tuned in
+    /// future versions.
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// assert!(Lzs::new(0x20) == Lzs::new(0x20).with_level(Lzs::DEFAULT_LEVEL));
+    /// let output = Lzs::new(0x20)
+    ///     .with_level(1)
+    ///     .compress(SliceReader::new(b"aaaabcdefabcdefg"), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// let input = Lzs::new(0x20)
+    ///     .decompress(SliceReader::new(&output), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// assert_eq!(input, b"aaaabcdefabcdefg");
+    /// ```
+    ///
+    /// # Panics
+    /// If the level is greater than 9.
+    #[must_use]
+    pub const fn with_level(self, level: u8) -> Self {
+        let insertion = if level == 0 {
+            Insertion::Skip
+        } else {
+            Insertion::All
+        };
+        let (finder, parser) = match level {
+            0 => (MatchFinder::HashChain { depth: 1 }, Parser::Greedy),
+            1 => (MatchFinder::HashChain { depth: 4 }, Parser::Greedy),
+            2 => (MatchFinder::HashChain { depth: 8 }, Parser::Greedy),
+            3 => (MatchFinder::HashChain { depth: 16 }, Parser::Greedy),
+            4 => (MatchFinder::HashChain { depth: 64 }, Parser::Greedy),
+            5 => (MatchFinder::HashChain { depth: 256 }, Parser::Greedy),
+            6 => (MatchFinder::Tree, Parser::Greedy),
+            7 => (MatchFinder::HashChain { depth: 256 }, Parser::Lazy),
+            8 => (MatchFinder::Tree, Parser::Lazy),
+            9 => (MatchFinder::Tree, Parser::Optimal),
+            _ => panic!("the level must be in 0..=9"),
+        };
+        Lzs {
+            finder,
+            insertion,
+            parser,
+            ..self
+        }
+    }
+
+    /// Select the [`MatchFinder`], [`Insertion`] and [`Parser`] reproducing a known encoder.
+    ///
+    /// All profiles use the greedy parser and add all positions, only the match finder differs.
+    ///
+    /// ```rust
+    /// # use lzs::{Compat, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let compress = |compat| {
+    ///     Lzs::new(0x20)
+    ///         .with_compat(compat)
+    ///         .compress(SliceReader::new(b"abc-abc-abc!abc"), VecWriter::with_capacity(30))
+    ///         .void_unwrap()
+    /// };
+    /// // the last "abc" refers to the first or the nearest one
+    /// assert_eq!(compress(Compat::Okumura)[8], 0xEE);
+    /// assert_eq!(compress(Compat::Nearest)[8], 0xF6);
+    /// ```
+    #[must_use]
+    pub const fn with_compat(self, compat: Compat) -> Self {
+        let finder = match compat {
+            Compat::Okumura => MatchFinder::Tree,
+            Compat::Nearest => MatchFinder::Bounded {
+                distance: (Self::n() - Self::f()) as u16,
+            },
+        };
+        Lzs {
+            finder,
+            insertion: Insertion::All,
+            parser: Parser::Greedy,
+            ..self
+        }
+    }
+
+    /// Use another [`MatchFinder`] for compression, the default is [`MatchFinder::Tree`].
+    #[inline]
+    #[must_use]
+    pub const fn with_match_finder(self, finder: MatchFinder) -> Self {
+        Lzs { finder, ..self }
+    }
+
+    /// The [`MatchFinder`] used for compression.
+    #[inline]
+    #[must_use]
+    pub const fn match_finder(&self) -> MatchFinder {
+        self.finder
+    }
+
+    /// Use another [`Insertion`] for compression, the default is [`Insertion::All`].
+    ///
+    /// ```rust
+    /// # use lzs::{Insertion, Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let input = [0u8; 1000];
+    /// let output = Lzs::new(0x20)
+    ///     .with_insertion(Insertion::Skip)
+    ///     .compress(SliceReader::new(&input), VecWriter::with_capacity(200))
+    ///     .void_unwrap();
+    /// assert_eq!(output.len(), 121);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_insertion(self, insertion: Insertion) -> Self {
+        Lzs { insertion, ..self }
+    }
+
+    /// The [`Insertion`] used for compression.
+    #[inline]
+    #[must_use]
+    pub const fn insertion(&self) -> Insertion {
+        self.insertion
+    }
+
+    /// Use another [`Parser`] for compression, the default is [`Parser::Greedy`].
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, Parser, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let my_lzs = Lzs::new(0x20).with_parser(Parser::Lazy);
+    /// let output = my_lzs
+    ///     .compress(SliceReader::new(b"aaaabcdefabcdefg"), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// let input = my_lzs
+    ///     .decompress(SliceReader::new(&output), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// assert_eq!(input, b"aaaabcdefabcdefg");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_parser(self, parser: Parser) -> Self {
+        Lzs { parser, ..self }
+    }
+
+    /// The [`Parser`] used for compression.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> Parser {
+        self.parser
     }
 
     #[inline(always)]
@@ -97,7 +374,7 @@ impl Lzs {
 
 #[cfg(all(test, feature = "alloc"))]
 mod tests {
-    use crate::dynamic::Lzs;
+    use crate::dynamic::{Compat, Insertion, Lzs, MatchFinder, Parser};
     use crate::slice::SliceReader;
     use crate::vec::VecWriter;
     use crate::void::ResultLzsErrorVoidExt;
@@ -150,4 +427,238 @@ mod tests {
             .void_unwrap();
         assert_eq!(output2.as_slice(), big_test_data);
     }
+
+    #[test]
+    fn test_compress_lazy() {
+        let lazy_lzs = TEST_LZS.with_parser(Parser::Lazy);
+        let big_test_data = include_bytes!("tree.rs");
+        let greedy = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let lazy = lazy_lzs
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert!(lazy.len() < greedy.len());
+        let output = lazy_lzs
+            .decompress(
+                SliceReader::new(&lazy),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert_eq!(output.as_slice(), big_test_data);
+
+        // "abc" matches, but "bcdefghij" is longer at the next position
+        let data = b"abcXbcdefghijYabcdefghij";
+        let greedy = TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(30))
+            .void_unwrap();
+        let lazy = lazy_lzs
+            .compress(SliceReader::new(data), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(lazy.len() + 1, greedy.len());
+        let optimal = TEST_LZS
+            .with_parser(Parser::Optimal)
+            .compress(SliceReader::new(data), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(optimal, lazy);
+        let output = lazy_lzs
+            .decompress(SliceReader::new(&lazy), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(output.as_slice(), data);
+    }
+
+    #[test]
+    fn test_compress_optimal() {
+        let optimal_lzs = TEST_LZS.with_parser(Parser::Optimal);
+        let big_test_data = include_bytes!("tree.rs");
+        let lazy = TEST_LZS
+            .with_parser(Parser::Lazy)
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let optimal = optimal_lzs
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert!(optimal.len() < lazy.len());
+        let output = optimal_lzs
+            .decompress(
+                SliceReader::new(&optimal),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert_eq!(output.as_slice(), big_test_data);
+
+        // "aaaa" as "a" and a match of 3 instead of "a", "a", "a", "a"
+        let output = optimal_lzs
+            .compress(SliceReader::new(b"aaaa"), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(output.len(), 4);
+    }
+
+    #[test]
+    fn test_compress_levels() {
+        let big_test_data = include_bytes!("tree.rs");
+        let mut sizes = [0; 10];
+        for level in 0..=9 {
+            let level_lzs = TEST_LZS.with_level(level);
+            let output = level_lzs
+                .compress(
+                    SliceReader::new(big_test_data),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            sizes[level as usize] = output.len();
+            let output = TEST_LZS
+                .decompress(
+                    SliceReader::new(&output),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            assert_eq!(output.as_slice(), big_test_data);
+        }
+        assert!(sizes[0] > sizes[Lzs::DEFAULT_LEVEL as usize] && sizes[8] > sizes[9]);
+        assert_eq!(TEST_LZS.with_level(6).match_finder(), MatchFinder::Tree);
+        assert_eq!(TEST_LZS.with_level(9).parser(), Parser::Optimal);
+    }
+
+    #[test]
+    fn test_compress_hash_chain() {
+        let big_test_data = include_bytes!("mod.rs");
+        let tree = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        for depth in [1, 16, 4096] {
+            let hash_chain_lzs = TEST_LZS.with_match_finder(MatchFinder::HashChain { depth });
+            let output = hash_chain_lzs
+                .compress(
+                    SliceReader::new(big_test_data),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            // the same longest matches are found when following all positions
+            assert_eq!(output.len() == tree.len(), depth == 4096);
+            let output = TEST_LZS
+                .decompress(
+                    SliceReader::new(&output),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            assert_eq!(output.as_slice(), big_test_data);
+        }
+    }
+
+    #[test]
+    fn test_compress_insertion() {
+        let big_test_data = include_bytes!("mod.rs");
+        for finder in [MatchFinder::Tree, MatchFinder::HashChain { depth: 16 }] {
+            for insertion in [Insertion::Every(0), Insertion::Every(3), Insertion::Skip] {
+                let insertion_lzs = TEST_LZS.with_match_finder(finder).with_insertion(insertion);
+                let output = insertion_lzs
+                    .compress(
+                        SliceReader::new(big_test_data),
+                        VecWriter::with_capacity(big_test_data.len()),
+                    )
+                    .void_unwrap();
+                let output = TEST_LZS
+                    .decompress(
+                        SliceReader::new(&output),
+                        VecWriter::with_capacity(big_test_data.len()),
+                    )
+                    .void_unwrap();
+                assert_eq!(output.as_slice(), big_test_data);
+            }
+        }
+
+        let every = TEST_LZS.with_insertion(Insertion::Every(1));
+        let output = every
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let all = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert_eq!(output, all);
+    }
+
+    #[test]
+    fn test_compress_compat() {
+        // the outputs of the encoders, see tests/data/README.md
+        let inputs: [(&[u8], [&[u8]; 2]); 2] = [
+            (
+                include_bytes!("../../tests/data/text.txt"),
+                [
+                    include_bytes!("../../tests/data/text.txt.lzss-c.lzs"),
+                    include_bytes!("../../tests/data/text.txt.lzss-brute.lzs"),
+                ],
+            ),
+            (
+                include_bytes!("../../tests/data/table.bin"),
+                [
+                    include_bytes!("../../tests/data/table.bin.lzss-c.lzs"),
+                    include_bytes!("../../tests/data/table.bin.lzss-brute.lzs"),
+                ],
+            ),
+        ];
+        for (input, outputs) in inputs {
+            for (compat, expected) in [Compat::Okumura, Compat::Nearest].into_iter().zip(outputs) {
+                let output = TEST_LZS
+                    .with_compat(compat)
+                    .compress(
+                        SliceReader::new(input),
+                        VecWriter::with_capacity(expected.len()),
+                    )
+                    .void_unwrap();
+                assert_eq!(output, expected, "{compat:?}");
+            }
+            assert_ne!(outputs[0], outputs[1]);
+        }
+    }
+
+    #[test]
+    fn test_compress_farthest() {
+        // the same lengths are found, only the positions differ
+        let big_test_data = include_bytes!("mod.rs");
+        let distance = (Lzs::n() - Lzs::f()) as u16;
+        let compress = |finder| {
+            TEST_LZS
+                .with_match_finder(finder)
+                .compress(
+                    SliceReader::new(big_test_data),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap()
+        };
+        let nearest = compress(MatchFinder::Bounded { distance });
+        let farthest = compress(MatchFinder::Farthest { distance });
+        assert_eq!(nearest.len(), farthest.len());
+        assert_ne!(nearest, farthest);
+        for output in [nearest, farthest] {
+            let output = TEST_LZS
+                .decompress(
+                    SliceReader::new(&output),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            assert_eq!(output.as_slice(), big_test_data);
+        }
+    }
 }
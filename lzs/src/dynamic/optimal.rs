@@ -0,0 +1,84 @@
+use crate::dynamic::code_buf::CodeBuf;
+use crate::dynamic::window::{Finder, Window};
+use crate::dynamic::Lzs;
+use crate::error::LzsError;
+use crate::read_write::{Read, Write};
+
+/// The cost of a literal in bits, including its flag.
+const LITERAL_COST: u16 = 9;
+/// The cost of a position-and-length pair in bits, including its flag.
+const PAIR_COST: u16 = 17;
+/// The number of positions compared at once.
+const CHUNK: usize = 4096;
+
+impl Lzs {
+    /// Choose the sequence of literals and matches with the smallest output.
+    ///
+    /// The input is read in chunks of [`CHUNK`] positions, keeping the longest match of every
+    /// position. Since every prefix of a match is a match too, the cheapest encoding of the
+    /// chunk is then computed from its end to its start. It is written up to the last `F`
+    /// positions, which are compared again with the next chunk.
+    pub(crate) fn compress_optimal<F: Finder, R: Read, W: Write>(
+        mut window: Window<F>,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        // The byte, the match position and the match length of every position.
+        let mut units = [(0u8, 0u16, 0u8); CHUNK];
+        // cost[i] is the number of bits to encode units[i..], length[i] the length of its first unit.
+        let mut cost = [0u16; CHUNK + 1];
+        let mut length = [1u8; CHUNK];
+        let mut filled = 0;
+        let mut code_buf = CodeBuf::new();
+
+        let (mut match_position, mut match_length) =
+            window.fill(reader).map_err(LzsError::ReadError)?;
+
+        loop {
+            while filled < CHUNK && window.len > 0 {
+                units[filled] = (
+                    window.current(),
+                    match_position as u16,
+                    match_length.min(window.len) as u8,
+                );
+                filled += 1;
+                (match_position, match_length) =
+                    window.advance(reader).map_err(LzsError::ReadError)?;
+            }
+            let last = window.len == 0;
+
+            cost[filled] = 0;
+            for i in (0..filled).rev() {
+                cost[i] = cost[i + 1] + LITERAL_COST;
+                length[i] = 1;
+                for len in Self::threshold() + 1..=(units[i].2 as usize).min(filled - i) {
+                    if cost[i + len] + PAIR_COST < cost[i] {
+                        cost[i] = cost[i + len] + PAIR_COST;
+                        length[i] = len as u8;
+                    }
+                }
+            }
+
+            let end = if last { filled } else { filled - Self::f() };
+            let mut i = 0;
+            while i < end {
+                let (c, match_position, _) = units[i];
+                let len = length[i] as usize;
+                if len == 1 {
+                    code_buf.literal(c, writer)
+                } else {
+                    code_buf.pair(match_position as usize, len, writer)
+                }
+                .map_err(LzsError::WriteError)?;
+                i += len;
+            }
+            units.copy_within(i..filled, 0);
+            filled -= i;
+            if last {
+                break;
+            }
+        }
+
+        code_buf.finish(writer).map_err(LzsError::WriteError)
+    }
+}
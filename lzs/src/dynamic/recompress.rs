@@ -0,0 +1,351 @@
+use crate::dynamic::code_buf::CodeBuf;
+use crate::dynamic::token::{Token, Tokens};
+use crate::dynamic::tree::Tree;
+use crate::dynamic::window::Window;
+use crate::dynamic::Lzs;
+use crate::read_write::Write;
+use crate::slice::SliceReader;
+use crate::vec::VecWriter;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use void::ResultVoidExt;
+
+/// The number of tokens written with one flag byte.
+const GROUP: usize = 8;
+
+/// The position in the buffer of the decoder which receives the byte at `offset` of the output.
+#[inline(always)]
+const fn ring(offset: usize) -> usize {
+    (Lzs::n() - Lzs::f() + offset) & (Lzs::n() - 1)
+}
+
+/// The new input with the decoder's view of it.
+struct Replay<'a> {
+    input: &'a [u8],
+    c: u8,
+}
+
+impl Replay<'_> {
+    /// The byte which the decoder reads from `pos` of its buffer before writing `offset`.
+    #[inline(always)]
+    fn byte(&self, offset: usize, pos: usize) -> u8 {
+        let back = match (ring(offset) + Lzs::n() - pos) & (Lzs::n() - 1) {
+            0 => Lzs::n(),
+            back => back,
+        };
+        offset
+            .checked_sub(back)
+            .map_or(self.c, |source| self.input[source])
+    }
+
+    /// Whether the token decodes to the input at `offset`.
+    fn decodes(&self, offset: usize, token: Token) -> bool {
+        match token {
+            Token::Literal(c) => self.input[offset] == c,
+            Token::Match { pos, len } => {
+                offset + len <= self.input.len()
+                    && (0..len).all(|i| {
+                        self.byte(offset + i, (pos + i) & (Lzs::n() - 1)) == self.input[offset + i]
+                    })
+            }
+        }
+    }
+}
+
+/// The old token at `offset`, whose match is moved by `shift` if it copies bytes from `moved` on.
+fn move_source(token: Token, offset: usize, moved: usize, shift: usize) -> Token {
+    match token {
+        Token::Match { pos, len } => {
+            let back = (ring(offset) + Lzs::n() - pos - 1) % Lzs::n() + 1;
+            if offset >= moved + back {
+                Token::Match {
+                    pos: (pos + shift) & (Lzs::n() - 1),
+                    len,
+                }
+            } else {
+                token
+            }
+        }
+        Token::Literal(_) => token,
+    }
+}
+
+/// Add `need` tokens to the run by shortening its matches at their ends, the cut bytes become
+/// literals. Returns `None` if the run has too few matches.
+fn lengthen(run: &[Token], input: &[u8], mut need: usize) -> Option<Vec<Token>> {
+    let mut tokens = Vec::with_capacity(run.len() + need);
+    let mut end = run.iter().map(Token::decompressed_len).sum::<usize>();
+    for token in run.iter().rev() {
+        end -= token.decompressed_len();
+        let cut = match *token {
+            Token::Match { len, .. } if need >= len - 1 => len,
+            Token::Match { len, .. } => need.min(len - Lzs::threshold() - 1),
+            Token::Literal(_) => 0,
+        };
+        if let Token::Match { pos, len } = *token {
+            tokens.extend(
+                input[end + len - cut..end + len]
+                    .iter()
+                    .rev()
+                    .map(|c| Token::Literal(*c)),
+            );
+            if cut < len {
+                tokens.push(Token::Match {
+                    pos,
+                    len: len - cut,
+                });
+            }
+            need -= cut.min(len - 1);
+        } else {
+            tokens.push(*token);
+        }
+    }
+    tokens.reverse();
+    (need == 0).then_some(tokens)
+}
+
+impl Lzs {
+    /// Compress the new input with the least changes to the compressed old input, e.g. to keep
+    /// the delta patch of a small edit small.
+    ///
+    /// The tokens of the old compressed data are kept where they still decode to the new
+    /// input: before the edit, and after it, where the positions of matches copying bytes after
+    /// the edit are moved by the change of the length. The other tokens are replaced by the
+    /// longest matches up to the start of the next old token, some of them shortened so that the
+    /// old tokens after them keep their place within the groups of eight sharing a flag byte.
+    ///
+    /// The result always decompresses to the new input, even if the old compressed data doesn't
+    /// belong to the old input. Since a match refers to a position of the buffer, an edit
+    /// changing the length also changes the bytes of the following matches copying bytes after
+    /// it, unless the length changes by a multiple of `N`.
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let lzs = Lzs::new(0x20);
+    /// let old: Vec<u8> = (0..1000)
+    ///     .flat_map(|i| format!("{i} squared is {}\n", i * i).into_bytes())
+    ///     .collect();
+    /// let old_compressed = lzs
+    ///     .compress(SliceReader::new(&old), VecWriter::with_capacity(old.len()))
+    ///     .void_unwrap();
+    /// let mut new = old.clone();
+    /// new[100..107].copy_from_slice(b"SQUARED");
+    /// let new_compressed = lzs.recompress(&old, &old_compressed, &new);
+    /// assert_eq!(lzs.decompress_vec(&new_compressed), new);
+    /// // only the compressed bytes near the edit change
+    /// assert_eq!(new_compressed[..40], old_compressed[..40]);
+    /// let tail = old_compressed.len() - 5000;
+    /// assert!(new_compressed.ends_with(&old_compressed[tail..]));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[must_use]
+    pub fn recompress(&self, old_input: &[u8], old_compressed: &[u8], new_input: &[u8]) -> Vec<u8> {
+        let prefix = old_input
+            .iter()
+            .zip(new_input)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let suffix = old_input
+            .iter()
+            .rev()
+            .zip(new_input.iter().rev())
+            .take(old_input.len().min(new_input.len()) - prefix)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let moved = old_input.len() - suffix;
+        let shift =
+            (Lzs::n() + new_input.len() % Lzs::n() - old_input.len() % Lzs::n()) & (Lzs::n() - 1);
+
+        // The old tokens with their offsets in the old input, and the offsets in the new input of
+        // the ones in the unchanged parts.
+        let mut old_tokens = Vec::new();
+        let mut offset = 0;
+        for (_, token) in Tokens::new(SliceReader::new(old_compressed)) {
+            if offset >= old_input.len() {
+                break;
+            }
+            old_tokens.push((offset, token));
+            offset += token.decompressed_len();
+        }
+        let starts: Vec<(usize, usize)> = old_tokens
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (offset, _))| {
+                if *offset < prefix {
+                    Some((*offset, i))
+                } else if *offset >= moved {
+                    Some((offset + new_input.len() - old_input.len(), i))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let matches = self.longest_matches(new_input);
+        let replay = Replay {
+            input: new_input,
+            c: self.c,
+        };
+        let mut tokens = Vec::with_capacity(old_tokens.len());
+        // The first new token since the last aligned old one and its offset.
+        let mut fresh = None;
+        let mut offset = 0;
+        let mut next = 0;
+        while offset < new_input.len() {
+            while starts.get(next).is_some_and(|(start, _)| *start < offset) {
+                next += 1;
+            }
+            let mut limit = starts
+                .get(next)
+                .map_or(new_input.len(), |(start, _)| *start);
+            if limit == offset {
+                let (old_offset, token) = old_tokens[starts[next].1];
+                let token = move_source(token, old_offset, moved, shift);
+                if replay.decodes(offset, token) {
+                    // Without a way to align the new tokens, the old one is taken as new one, the
+                    // next old token may get aligned by shortening it.
+                    if let Some((first, start)) = fresh {
+                        let need = (starts[next].1 + GROUP - tokens.len() % GROUP) % GROUP;
+                        if let Some(run) = lengthen(&tokens[first..], &new_input[start..], need) {
+                            tokens.truncate(first);
+                            tokens.extend(run);
+                            fresh = None;
+                        }
+                    }
+                    tokens.push(token);
+                    offset += token.decompressed_len();
+                    continue;
+                }
+                limit = starts
+                    .get(next + 1)
+                    .map_or(new_input.len(), |(start, _)| *start);
+            }
+
+            fresh.get_or_insert((tokens.len(), offset));
+            let (pos, len) = matches[offset];
+            let len = (len as usize).min(limit - offset);
+            let token = if len > Self::threshold() {
+                Token::Match {
+                    pos: pos as usize,
+                    len,
+                }
+            } else {
+                Token::Literal(new_input[offset])
+            };
+            tokens.push(token);
+            offset += token.decompressed_len();
+        }
+
+        let mut writer = VecWriter::with_capacity(old_compressed.len());
+        let mut code_buf = CodeBuf::new();
+        for token in tokens {
+            match token {
+                Token::Literal(c) => code_buf.literal(c, &mut writer),
+                Token::Match { pos, len } => code_buf.pair(pos, len, &mut writer),
+            }
+            .void_unwrap();
+        }
+        code_buf.finish(&mut writer).void_unwrap();
+        writer.finish().void_unwrap()
+    }
+
+    /// The position and the length of the longest match of every position of the input.
+    fn longest_matches(self, input: &[u8]) -> Vec<(u16, u8)> {
+        let mut matches = Vec::with_capacity(input.len());
+        let mut reader = SliceReader::new(input);
+        let mut window = Window::new(self.c, Tree::new(), 1, 0);
+        let (mut match_position, mut match_length) = window.fill(&mut reader).void_unwrap();
+        while window.len > 0 {
+            matches.push((match_position as u16, match_length.min(window.len) as u8));
+            (match_position, match_length) = window.advance(&mut reader).void_unwrap();
+        }
+        matches
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::recompress::lengthen;
+    use crate::dynamic::{Lzs, Token};
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap()
+    }
+
+    #[test]
+    fn test_recompress_unchanged() {
+        let big_test_data = include_bytes!("mod.rs");
+        for data in [&big_test_data[..], &[0; 5000], b"", b"a"] {
+            let compressed = compress(data);
+            assert_eq!(TEST_LZS.recompress(data, &compressed, data), compressed);
+        }
+    }
+
+    #[test]
+    fn test_recompress_edited() {
+        let old = include_bytes!("mod.rs");
+        let old_compressed = compress(old);
+        let at = old.len() / 8;
+        let tail = &old_compressed[old_compressed.len() / 2..];
+
+        let mut new = old.to_vec();
+        new[at..at + 4].copy_from_slice(b"EDIT");
+        let new_compressed = TEST_LZS.recompress(old, &old_compressed, &new);
+        assert_eq!(TEST_LZS.decompress_vec(&new_compressed), new);
+        assert_eq!(new_compressed[..at / 4], old_compressed[..at / 4]);
+        assert!(new_compressed.ends_with(tail));
+
+        // the positions of the matches only stay when the length changes by a multiple of `N`
+        for inserted in [1, 5, Lzs::n()] {
+            let mut new = old.to_vec();
+            new.splice(at..at, (0..inserted).map(|i| i as u8));
+            let new_compressed = TEST_LZS.recompress(old, &old_compressed, &new);
+            assert_eq!(TEST_LZS.decompress_vec(&new_compressed), new);
+            assert_eq!(new_compressed[..at / 4], old_compressed[..at / 4]);
+            assert_eq!(new_compressed.ends_with(tail), inserted == Lzs::n());
+        }
+        let mut new = old.to_vec();
+        new.drain(at..at + 100);
+        let new_compressed = TEST_LZS.recompress(old, &old_compressed, &new);
+        assert_eq!(TEST_LZS.decompress_vec(&new_compressed), new);
+    }
+
+    #[test]
+    fn test_recompress_unrelated() {
+        // the old compressed data doesn't belong to the old input
+        let new = include_bytes!("mod.rs");
+        let old = &new[100..];
+        for old_compressed in [compress(&new[..5000]), vec![0x55; 3000], Vec::new()] {
+            let new_compressed = TEST_LZS.recompress(old, &old_compressed, new);
+            assert_eq!(TEST_LZS.decompress_vec(&new_compressed), new);
+        }
+    }
+
+    #[test]
+    fn test_lengthen() {
+        let input = b"abcdef";
+        let run = [Token::Literal(b'a'), Token::Match { pos: 0, len: 5 }];
+        assert_eq!(lengthen(&run, input, 0).as_deref(), Some(&run[..]));
+        assert_eq!(
+            lengthen(&run, input, 2),
+            Some(vec![
+                Token::Literal(b'a'),
+                Token::Match { pos: 0, len: 3 },
+                Token::Literal(b'e'),
+                Token::Literal(b'f'),
+            ])
+        );
+        assert_eq!(
+            lengthen(&run, input, 4),
+            Some(input.iter().map(|c| Token::Literal(*c)).collect())
+        );
+        assert_eq!(lengthen(&run, input, 3), None);
+    }
+}
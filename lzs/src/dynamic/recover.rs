@@ -0,0 +1,542 @@
+use crate::dynamic::{Lzs, Token, Tokens};
+use crate::slice::SliceReader;
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+const TAIL: usize = Recovered::TAIL;
+/// The number of tokens decoded to confirm a resynchronization.
+const HORIZON: usize = 8192;
+/// The number of tokens decoded under every position, before the remaining ones are compared.
+const PREFIX: usize = 256;
+/// The number of flag bytes tried after a derailment.
+const MAX_OFFSETS: usize = 4096;
+/// The number of passed checks needed to confirm a resynchronization.
+const MIN_CHECKS: usize = 32;
+/// The number of tokens decoded while searching each resynchronization.
+const BUDGET: usize = 1 << 24;
+
+/// A part of the input decompressed by [`Lzs::decompress_recover`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RecoveredRange {
+    /// The compressed bytes, starting with a flag byte.
+    pub input: Range<usize>,
+    /// The decompressed bytes in [`Recovered::output`].
+    pub output: Range<usize>,
+    /// The number of decompressed bytes copied from lost data or from the last bytes before a
+    /// derailment, which may be wrong.
+    pub unknown: usize,
+}
+
+/// The result of [`Lzs::decompress_recover`].
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
+pub struct Recovered {
+    /// The decompressed bytes of all ranges, one after the other.
+    pub output: Vec<u8>,
+    /// The parts of the input which look undamaged, in order.
+    pub ranges: Vec<RecoveredRange>,
+}
+
+impl Recovered {
+    /// The number of bytes before a derailment which are assumed to be wrong, at the end of
+    /// every range but the last one.
+    pub const TAIL: usize = 2048;
+}
+
+/// The buffer of the decoder, with the bytes which are known.
+struct History {
+    buf: Vec<u8>,
+    known: Vec<bool>,
+}
+
+/// The result of a token for the heuristics.
+enum Check {
+    None,
+    /// The byte after a match differs from the byte after its source.
+    Passed,
+    /// The match could have been longer.
+    Failed,
+    /// The match refers to a position which the encoder never had in its window.
+    Invalid,
+}
+
+/// The output decoded since the start or a resynchronization.
+struct Segment<'a> {
+    history: &'a History,
+    /// The position of the first byte in the buffer.
+    start: usize,
+    /// The positions of the buffer which were lost, before the first byte is written.
+    lost: (usize, usize),
+    output: Vec<u8>,
+    known: Vec<bool>,
+    /// The position after the source of the last match, if it is shorter than `F`.
+    next: Option<usize>,
+    /// The input offset and output length of each flag byte.
+    groups: Vec<(usize, usize)>,
+}
+
+impl<'a> Segment<'a> {
+    fn new(history: &'a History, start: usize, lost: (usize, usize)) -> Self {
+        Segment {
+            history,
+            start,
+            lost,
+            output: Vec::new(),
+            known: Vec::new(),
+            next: None,
+            groups: Vec::new(),
+        }
+    }
+
+    /// Start again at another position, keeping the buffers.
+    fn reset(&mut self, start: usize, lost: (usize, usize)) {
+        self.start = start;
+        self.lost = lost;
+        self.output.clear();
+        self.known.clear();
+        self.next = None;
+        self.groups.clear();
+    }
+
+    /// Decode the tokens from the start, with the number of passed checks, unless one fails.
+    fn replay(&mut self, start: usize, lost: (usize, usize), tokens: &[Token]) -> Option<usize> {
+        self.reset(start, lost);
+        let mut checks = 0;
+        for token in tokens {
+            match self.push(*token) {
+                Check::Passed => checks += 1,
+                Check::None => {}
+                Check::Failed | Check::Invalid => return None,
+            }
+        }
+        Some(checks)
+    }
+
+    /// The byte at a position of the buffer, and whether it is known.
+    fn byte(&self, pos: usize) -> (u8, bool) {
+        let mask = Lzs::n() - 1;
+        let len = self.output.len();
+        let distance = match (self.start + len + Lzs::n() - pos) & mask {
+            0 => Lzs::n(),
+            distance => distance,
+        };
+        if distance <= len {
+            (self.output[len - distance], self.known[len - distance])
+        } else if (pos + Lzs::n() - self.lost.0) & mask < self.lost.1 {
+            (self.history.buf[pos], false)
+        } else {
+            (self.history.buf[pos], self.history.known[pos])
+        }
+    }
+
+    /// Decode a token.
+    ///
+    /// A match is never followed by the byte after its source in streams of greedy or lazy
+    /// encoders, since they take the longest match found.
+    fn push(&mut self, token: Token) -> Check {
+        let first = match token {
+            Token::Literal(c) => (c, true),
+            Token::Match { pos, .. } => self.byte(pos),
+        };
+        let mut check = Check::None;
+        if let Some(next) = self.next.take() {
+            let (c, known) = self.byte(next);
+            if known && first.1 {
+                check = if c == first.0 {
+                    Check::Failed
+                } else {
+                    Check::Passed
+                };
+            }
+        }
+
+        match token {
+            Token::Literal(c) => {
+                self.output.push(c);
+                self.known.push(true);
+            }
+            Token::Match { pos, len } => {
+                let mask = Lzs::n() - 1;
+                let ring = (self.start + self.output.len()) & mask;
+                let distance = match (ring + Lzs::n() - pos) & mask {
+                    0 => Lzs::n(),
+                    distance => distance,
+                };
+                for i in 0..len {
+                    let (c, known) = self.byte((pos + i) & mask);
+                    self.output.push(c);
+                    self.known.push(known);
+                }
+                if len < Lzs::f() {
+                    self.next = Some((pos + len) & mask);
+                }
+                if distance > Lzs::n() - Lzs::f() {
+                    check = Check::Invalid;
+                }
+            }
+        }
+        check
+    }
+
+    /// Decode the input until it derails.
+    ///
+    /// Returns the length of the input and output before the flag byte of the first
+    /// suspicious token, or of the whole input, and whether it derailed.
+    fn decode(&mut self, input: &[u8]) -> (usize, usize, bool) {
+        let mut end = 0;
+        for (count, (offset, token)) in Tokens::new(SliceReader::new(input)).enumerate() {
+            if count % 8 == 0 {
+                self.groups.push((offset - 1, self.output.len()));
+            }
+            if let Check::Failed | Check::Invalid = self.push(token) {
+                let (end, len) = self.groups[self.groups.len() - 1];
+                return (end, len, true);
+            }
+            end = offset + token.compressed_len();
+        }
+        (end, self.output.len(), false)
+    }
+
+    /// The input and output length of the last flag byte within the first `len` bytes.
+    fn truncate(&self, len: usize) -> (usize, usize) {
+        let groups = self.groups.partition_point(|(_, output)| *output <= len);
+        self.groups[..groups].last().copied().unwrap_or((0, 0))
+    }
+
+    /// The buffer after the first `len` bytes, with the last `TAIL` ones unknown.
+    fn history(&self, len: usize) -> History {
+        let mask = Lzs::n() - 1;
+        let mut history = History {
+            buf: self.history.buf.clone(),
+            known: self.history.known.clone(),
+        };
+        for i in 0..self.lost.1 {
+            history.known[(self.lost.0 + i) & mask] = false;
+        }
+        for (i, (c, known)) in self.output[..len].iter().zip(&self.known).enumerate() {
+            history.buf[(self.start + i) & mask] = *c;
+            history.known[(self.start + i) & mask] = *known && i + TAIL < len;
+        }
+        for i in 1..=TAIL.saturating_sub(len) {
+            history.known[(self.start + Lzs::n() - i) & mask] = false;
+        }
+        history
+    }
+}
+
+impl Lzs {
+    /// Decompress a damaged stream, skipping the parts which look corrupted.
+    ///
+    /// The decoding is considered derailed at a match referring to a position which the
+    /// encoder never had in its window, or at a match followed by the byte after its source,
+    /// since greedy and lazy encoders take the longest match found. The flag byte before it and
+    /// the following bytes are skipped: the next 4096 flag bytes, when the tokens are read from
+    /// the byte after it, are tried as the start of the stream, and the position of the decoder
+    /// is searched among the ones under which the next 256 tokens, then 8192 tokens look
+    /// valid, unless no position is clearly better than the others. The last
+    /// [`TAIL`](Recovered::TAIL) bytes before a derailment and the lost bytes are unknown
+    /// afterwards, the other bytes of the buffer are kept, so the lost part is assumed to be
+    /// shorter than `N`. The density of the tokens isn't checked: the tokens read from random
+    /// bytes are as dense as the ones of a stream, about one per byte.
+    ///
+    /// The search after a derailment gives up, ending the recovery, once it has decoded 2^24
+    /// tokens, so each derailment costs at most about a second. A few lost bytes usually take a
+    /// small part of it, 4 KiB of random bytes about a sixth.
+    ///
+    /// The last bytes of a range and the first bytes of the next one may be wrong, like the
+    /// bytes copied from unknown ones. Streams of [`Parser::Optimal`](crate::Parser::Optimal),
+    /// whose matches are not always the longest, are cut at the first shorter one. An
+    /// undamaged stream of a greedy or lazy encoder is returned as one range, like
+    /// [`decompress_vec`](Lzs::decompress_vec).
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, Recovered, ResultLzsErrorVoidExt, SliceReader, VecWriter};
+    /// let lzs = Lzs::new(0x20);
+    /// let data: Vec<u8> = (0..3000u32)
+    ///     .flat_map(|i| format!("{} ", i * i * 7 % 10007).into_bytes())
+    ///     .collect();
+    /// let mut input = lzs
+    ///     .compress(SliceReader::new(&data), VecWriter::with_capacity(data.len()))
+    ///     .void_unwrap();
+    /// let recovered = lzs.decompress_recover(&input);
+    /// assert_eq!(recovered.output, data);
+    /// assert_eq!(recovered.ranges.len(), 1);
+    ///
+    /// // lose some bytes
+    /// input.drain(3000..3003);
+    /// let recovered = lzs.decompress_recover(&input);
+    /// assert_eq!(recovered.ranges.len(), 2);
+    /// let (first, last) = (&recovered.ranges[0], &recovered.ranges[1]);
+    /// assert!(data.starts_with(&recovered.output[..first.output.end - Recovered::TAIL]));
+    /// assert_eq!(last.input.end, input.len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[must_use]
+    pub fn decompress_recover(&self, input: &[u8]) -> Recovered {
+        self.recover(input, BUDGET).0
+    }
+
+    /// Decompress a damaged stream, decoding at most `budget` tokens to search each
+    /// resynchronization, with the number of tokens decoded to search them.
+    fn recover(self, input: &[u8], budget: usize) -> (Recovered, usize) {
+        let mask = Self::n() - 1;
+        let mut recovered = Recovered::default();
+        let mut history = History {
+            buf: vec![self.c; Self::n()],
+            known: vec![true; Self::n()],
+        };
+        let mut start = 0;
+        let mut ring = Self::n() - Self::f();
+        let mut lost = (0, 0);
+        let mut work = 0;
+
+        while start < input.len() {
+            let mut segment = Segment::new(&history, ring, lost);
+            let (mut end, mut len, derailed) = segment.decode(&input[start..]);
+            let next = segment.history(len);
+            let tail = (ring + len + Self::n() - TAIL) & mask;
+            let mut resync = None;
+            if let Some((offset, skipped)) = derailed
+                .then(|| {
+                    let mut left = budget;
+                    let resync = Self::resync(&next, tail, input, start + end.max(1), &mut left);
+                    work += budget - left;
+                    resync
+                })
+                .flatten()
+            {
+                // the decoder went on after the lost bytes
+                if skipped < TAIL {
+                    (end, len) = segment.truncate(len.saturating_sub(TAIL - skipped));
+                }
+                resync = Some((offset, (tail + skipped) & mask, (tail, skipped.max(TAIL))));
+            }
+            if end > 0 {
+                let output = recovered.output.len();
+                recovered.output.extend_from_slice(&segment.output[..len]);
+                recovered.ranges.push(RecoveredRange {
+                    input: start..start + end,
+                    output: output..output + len,
+                    unknown: segment.known[..len].iter().filter(|known| !**known).count(),
+                });
+            }
+            history = next;
+
+            let Some((offset, position, unknown)) = resync else {
+                break;
+            };
+            start = offset;
+            ring = position;
+            lost = unknown;
+        }
+
+        (recovered, work)
+    }
+
+    /// Find the next offset at which the tokens look valid, and the number of bytes after
+    /// `tail` at which the decoder continues.
+    ///
+    /// The tokens read from `from` meet the flag bytes of the stream after the damaged part
+    /// within a few groups, so only their flag bytes are tried. Gives up before decoding more
+    /// than `budget` tokens, which is reduced by the decoded ones.
+    fn resync(
+        history: &History,
+        tail: usize,
+        input: &[u8],
+        from: usize,
+        budget: &mut usize,
+    ) -> Option<(usize, usize)> {
+        let (n, mask) = (Self::n(), Self::n() - 1);
+        let mut segment = Segment::new(history, 0, (0, 0));
+        let mut tokens = Vec::with_capacity(HORIZON);
+        let mut invalid = vec![false; n];
+        let mut candidates = Vec::new();
+        let flags = Tokens::new(SliceReader::new(&input[from..]))
+            .step_by(8)
+            .map(|(offset, _)| from + offset - 1)
+            .take(MAX_OFFSETS);
+        for offset in flags {
+            tokens.clear();
+            tokens.extend(
+                Tokens::new(SliceReader::new(&input[offset..]))
+                    .take(HORIZON)
+                    .map(|(_, token)| token),
+            );
+            *budget = budget.checked_sub(tokens.len())?;
+            let required = |tokens: usize| MIN_CHECKS.min(tokens / 8);
+            let prefix = &tokens[..tokens.len().min(PREFIX)];
+
+            // the positions under which a match refers to a position beyond the window
+            invalid.fill(false);
+            let mut len = 0;
+            for token in &tokens {
+                if let Token::Match { pos, .. } = token {
+                    for distance in n - Self::f() + 1..=n {
+                        invalid[(pos + distance + n - (len & mask)) & mask] = true;
+                    }
+                }
+                len += token.decompressed_len();
+            }
+
+            // the positions under which the prefix looks valid, the nearest one first
+            candidates.clear();
+            for skipped in 0..n {
+                let position = (tail + skipped) & mask;
+                if !invalid[position] {
+                    let lost = (tail, skipped.max(TAIL));
+                    *budget = budget.checked_sub(prefix.len())?;
+                    if let Some(checks) = segment.replay(position, lost, prefix) {
+                        candidates.push((checks, skipped));
+                    }
+                }
+            }
+            // a clear winner of the prefix only has to stay valid
+            if let Some((_, skipped)) = clear_winner(&candidates, required(prefix.len())) {
+                let lost = (tail, skipped.max(TAIL));
+                *budget = budget.checked_sub(tokens.len())?;
+                let checks = segment.replay((tail + skipped) & mask, lost, &tokens);
+                if checks.is_some_and(|checks| checks >= required(tokens.len())) {
+                    return Some((offset, skipped));
+                }
+            }
+            if tokens.len() > prefix.len() {
+                for candidate in &mut candidates {
+                    let (_, skipped) = *candidate;
+                    let lost = (tail, skipped.max(TAIL));
+                    *budget = budget.checked_sub(tokens.len())?;
+                    let checks = segment.replay((tail + skipped) & mask, lost, &tokens);
+                    candidate.0 = checks.unwrap_or(0);
+                }
+                if let Some((_, skipped)) = clear_winner(&candidates, required(tokens.len())) {
+                    return Some((offset, skipped));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// The candidate with the most passed checks, the first one if equal, if it has at least
+/// `required` ones and more than twice as many as any other one.
+fn clear_winner(candidates: &[(usize, usize)], required: usize) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    let mut second = 0;
+    for &(checks, skipped) in candidates {
+        if best.is_none_or(|(best, _)| checks > best) {
+            second = best.map_or(0, |(best, _)| best);
+            best = Some((checks, skipped));
+        } else {
+            second = second.max(checks);
+        }
+    }
+    best.filter(|(checks, _)| *checks >= required && *checks > 2 * second)
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::recover::{BUDGET, PREFIX, TAIL};
+    use crate::dynamic::Lzs;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    /// Text files of this repository, whose copy doesn't change with them.
+    const BIG_TEST_DATA: &[u8] = include_bytes!("../../tests/data/recover.txt");
+
+    /// The garbage bytes of a damaged stream.
+    fn garbage(len: usize) -> impl Iterator<Item = u8> {
+        let mut state = 1u32;
+        (0..len).map(move |_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+    }
+
+    #[test]
+    fn test_decompress_recover() {
+        let big_test_data = BIG_TEST_DATA;
+        for lzs in [TEST_LZS, TEST_LZS.with_level(0), TEST_LZS.with_level(8)] {
+            let compressed = lzs
+                .compress(
+                    SliceReader::new(big_test_data),
+                    VecWriter::with_capacity(big_test_data.len()),
+                )
+                .void_unwrap();
+            let recovered = lzs.decompress_recover(&compressed);
+            assert_eq!(recovered.output, big_test_data);
+            assert_eq!(recovered.ranges.len(), 1);
+            assert_eq!(recovered.ranges[0].input, 0..compressed.len());
+            assert_eq!(recovered.ranges[0].unknown, 0);
+        }
+        assert!(TEST_LZS.decompress_recover(&[]).ranges.is_empty());
+    }
+
+    #[test]
+    fn test_decompress_recover_damaged() {
+        let big_test_data = BIG_TEST_DATA;
+        let compressed = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let middle = compressed.len() / 2;
+        for lost in [1, 3, 100] {
+            let mut input = compressed.clone();
+            input.drain(middle..middle + lost);
+            let recovered = TEST_LZS.decompress_recover(&input);
+            assert_eq!(recovered.ranges.len(), 2);
+            let (first, last) = (&recovered.ranges[0], &recovered.ranges[1]);
+            assert_eq!(last.input.end, input.len());
+            // apart from the last bytes, which become unknown at a derailment, the first range is
+            // right
+            let output = &recovered.output[first.output.start..first.output.end - TAIL];
+            assert!(big_test_data.starts_with(output));
+            // the bytes of the last range not copied from unknown ones and most others are
+            // right, where they end with the data
+            let output = &recovered.output[last.output.clone()];
+            let data = &big_test_data[big_test_data.len() - output.len()..];
+            let right = output.iter().zip(data).filter(|(a, b)| a == b).count();
+            assert!(right >= output.len() - last.unknown);
+            assert!(right > output.len() * 3 / 4);
+        }
+    }
+
+    #[test]
+    fn test_decompress_recover_garbage() {
+        let big_test_data = BIG_TEST_DATA;
+        let compressed = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let middle = compressed.len() / 2;
+        let mut input = compressed.clone();
+        input.splice(middle..middle, garbage(4096));
+
+        let (recovered, work) = TEST_LZS.recover(&input, BUDGET);
+        let first = &recovered.ranges[0];
+        let output = &recovered.output[first.output.start..first.output.end - TAIL];
+        assert!(big_test_data.starts_with(output));
+        let last = recovered.ranges.last().unwrap();
+        assert_eq!(last.input.end, input.len());
+        assert!(last.input.start < middle + 4096 + 1000);
+        let output = &recovered.output[last.output.clone()];
+        let data = &big_test_data[big_test_data.len() - output.len()..];
+        let right = output.iter().zip(data).filter(|(a, b)| a == b).count();
+        assert!(right >= output.len() - last.unknown);
+        assert!(right > output.len() * 3 / 5);
+        // not every garbage byte is tried with every position of the decoder
+        assert!(work <= BUDGET / 4);
+
+        // the search gives up when it runs out of budget
+        let (recovered, work) = TEST_LZS.recover(&input, PREFIX);
+        assert_eq!(recovered.ranges.len(), 1);
+        assert!(work <= PREFIX);
+    }
+}
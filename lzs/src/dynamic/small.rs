@@ -0,0 +1,175 @@
+use crate::dynamic::code_buf::CodeBuf;
+use crate::dynamic::window::compare;
+use crate::dynamic::Lzs;
+use crate::error::LzsError;
+use crate::macros::{get, set};
+use crate::read_write::{Read, Write};
+
+/// The size of the ring buffer of [`Lzs::compress_small`].
+const SMALL_N: usize = 256;
+
+impl Lzs {
+    /// The maximal distance of the matches found by [`Lzs::compress_small`].
+    pub const SMALL_DISTANCE: usize = SMALL_N - Self::f();
+
+    /// Compress the input data into the output, with a working set of less than 300 bytes.
+    ///
+    /// Only the last [`SMALL_DISTANCE`](Lzs::SMALL_DISTANCE) bytes are searched for matches,
+    /// without any index, and the [`MatchFinder`](crate::MatchFinder), [`Insertion`](crate::Insertion)
+    /// and [`Parser`](crate::Parser) are ignored. The output is decoded like any other.
+    ///
+    /// Nothing is allocated, the buffer is on the stack.
+    ///
+    /// ```rust
+    /// # use lzs::{Lzs, ResultLzsErrorVoidExt, ResultLzsErrorVoidReadExt, SliceReader, SliceWriter, VecWriter};
+    /// let mut output = [0; 30];
+    /// let len = Lzs::new(0x20)
+    ///     .compress_small(SliceReader::new(b"Example Data, Example Data"), SliceWriter::new(&mut output))
+    ///     .void_read_unwrap()
+    ///     .unwrap();
+    /// let input = Lzs::new(0x20)
+    ///     .decompress(SliceReader::new(&output[..len]), VecWriter::with_capacity(30))
+    ///     .void_unwrap();
+    /// assert_eq!(input, b"Example Data, Example Data");
+    /// ```
+    pub fn compress_small<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+    ) -> Result<W::Output, LzsError<R::Error, W::Error>> {
+        self.compress_small_internal(&mut reader, &mut writer)?;
+        writer.finish().map_err(LzsError::WriteError)
+    }
+
+    #[inline(always)]
+    fn compress_small_internal<R: Read, W: Write>(
+        self,
+        reader: &mut R,
+        writer: &mut W,
+    ) -> Result<(), LzsError<R::Error, W::Error>> {
+        // Like text_buf, but only the last bytes; not yet written bytes are C like in the decoder.
+        let mut buf = [self.c; SMALL_N + Self::f() - 1];
+        let mut code_buf = CodeBuf::new();
+        let mut s = 0;
+        let mut r = SMALL_N - Self::f();
+        let mut len = 0;
+        // The position of r in the buffer of the decoder.
+        let mut position = Self::n() - Self::f();
+
+        while len < Self::f() {
+            if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
+                set!(buf, r + len, c);
+            } else {
+                break;
+            }
+
+            len += 1;
+        }
+
+        while len > 0 {
+            let mut match_distance = 0;
+            let mut match_length = 0;
+            let first = get!(buf, r);
+            for d in 1..=Self::SMALL_DISTANCE {
+                let p = (r + SMALL_N - d) & (SMALL_N - 1);
+                if get!(buf, p) != first {
+                    continue;
+                }
+
+                let (i, _) = compare(&buf, r, p);
+
+                if i > match_length {
+                    match_distance = d;
+                    match_length = i;
+
+                    if match_length >= Self::f() {
+                        break;
+                    }
+                }
+            }
+
+            if match_length > len {
+                match_length = len;
+            }
+
+            if match_length <= Self::threshold() {
+                match_length = 1;
+                code_buf
+                    .literal(first, writer)
+                    .map_err(LzsError::WriteError)?;
+            } else {
+                code_buf
+                    .pair(
+                        (position + Self::n() - match_distance) & (Self::n() - 1),
+                        match_length,
+                        writer,
+                    )
+                    .map_err(LzsError::WriteError)?;
+            }
+
+            for _ in 0..match_length {
+                if let Some(c) = reader.read().map_err(LzsError::ReadError)? {
+                    set!(buf, s, c);
+
+                    if s < Self::f() - 1 {
+                        set!(buf, s + SMALL_N, c);
+                    }
+                } else {
+                    len -= 1;
+                }
+
+                s = (s + 1) & (SMALL_N - 1);
+                r = (r + 1) & (SMALL_N - 1);
+                position = (position + 1) & (Self::n() - 1);
+            }
+        }
+
+        code_buf.finish(writer).map_err(LzsError::WriteError)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    #[test]
+    fn test_compress_small() {
+        let big_test_data = include_bytes!("mod.rs");
+        let output = TEST_LZS
+            .compress_small(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert!(output.len() < big_test_data.len() * 3 / 4);
+        let output = TEST_LZS
+            .decompress(
+                SliceReader::new(&output),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        assert_eq!(output.as_slice(), big_test_data);
+    }
+
+    #[test]
+    fn test_compress_small_prefill() {
+        // the spaces are found in the initial buffer, like the original compressor
+        let data = b"                  a  b";
+        let output = TEST_LZS
+            .compress_small(SliceReader::new(data), VecWriter::with_capacity(30))
+            .void_unwrap();
+        let expected = TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(output.len(), expected.len());
+        let output = TEST_LZS
+            .decompress(SliceReader::new(&output), VecWriter::with_capacity(30))
+            .void_unwrap();
+        assert_eq!(output.as_slice(), data);
+    }
+}
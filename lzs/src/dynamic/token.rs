@@ -0,0 +1,298 @@
+use crate::dynamic::code_buf::CodeBuf;
+use crate::dynamic::Lzs;
+use crate::error::TokenWriteError;
+use crate::read_write::{Read, Write};
+use void::Void;
+
+/// A unit of a compressed stream.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Token {
+    /// An unencoded byte.
+    Literal(u8),
+    /// A copy of `len` bytes (in `THRESHOLD + 1..=F`) from the position `pos` (below `N`) of the
+    /// buffer of the decoder.
+    Match {
+        /// The position in the buffer of the decoder, which starts at `N - F`.
+        pos: usize,
+        /// The number of copied bytes.
+        len: usize,
+    },
+}
+
+impl Token {
+    /// The number of decompressed bytes.
+    #[inline]
+    #[must_use]
+    pub const fn decompressed_len(&self) -> usize {
+        match self {
+            Token::Literal(_) => 1,
+            Token::Match { len, .. } => *len,
+        }
+    }
+
+    /// The number of compressed bytes, without the flag.
+    #[inline]
+    #[must_use]
+    pub const fn compressed_len(&self) -> usize {
+        match self {
+            Token::Literal(_) => 1,
+            Token::Match { .. } => 2,
+        }
+    }
+}
+
+/// Read the next token, the flags are kept between the calls like in `Decoder`.
+///
+/// Returns `None` at the end of the input, an incomplete token at the end is dropped.
+#[inline(always)]
+pub(crate) fn read_token<R: Read>(
+    reader: &mut R,
+    flags: &mut usize,
+) -> Result<Option<Token>, R::Error> {
+    *flags >>= 1;
+
+    if (*flags & 256) == 0 {
+        if let Some(c) = reader.read()? {
+            *flags = c as usize | 0xFF00;
+        } else {
+            return Ok(None);
+        }
+    }
+
+    if (*flags & 1) != 0 {
+        Ok(reader.read()?.map(Token::Literal))
+    } else if let (Some(c1), Some(c2)) = (reader.read()?, reader.read()?) {
+        Ok(Some(Token::Match {
+            pos: c1 as usize | ((c2 as usize & 0xF0) << 4),
+            len: (c2 as usize & 0x0F) + Lzs::threshold() + 1,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Iterates the tokens of a compressed stream with their offsets in it, without decompressing.
+///
+/// With a reader which can't fail (like [`SliceReader`](crate::SliceReader)) it is an
+/// [`Iterator`], otherwise use [`next_token`](Tokens::next_token).
+///
+/// ```rust
+/// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, Token, Tokens, VecWriter};
+/// let input = Lzs::new(0x20)
+///     .compress(SliceReader::new(b"abcabcabc"), VecWriter::with_capacity(30))
+///     .void_unwrap();
+/// let tokens: Vec<_> = Tokens::new(SliceReader::new(&input)).collect();
+/// assert_eq!(
+///     tokens,
+///     [
+///         (1, Token::Literal(b'a')),
+///         (2, Token::Literal(b'b')),
+///         (3, Token::Literal(b'c')),
+///         (4, Token::Match { pos: 0xFEE, len: 6 }),
+///     ]
+/// );
+/// ```
+pub struct Tokens<R> {
+    reader: R,
+    flags: usize,
+    offset: usize,
+}
+
+impl<R: Read> Tokens<R> {
+    /// Iterate the tokens of the reader.
+    #[inline]
+    #[must_use]
+    pub const fn new(reader: R) -> Self {
+        Tokens {
+            reader,
+            flags: 0,
+            offset: 0,
+        }
+    }
+
+    /// The number of bytes read so far.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Read the next token and its offset in the input.
+    pub fn next_token(&mut self) -> Result<Option<(usize, Token)>, R::Error> {
+        let mut reader = CountReader {
+            reader: &mut self.reader,
+            count: &mut self.offset,
+        };
+        Ok(read_token(&mut reader, &mut self.flags)?
+            .map(|token| (self.offset - token.compressed_len(), token)))
+    }
+}
+
+impl<R: Read<Error = Void>> Iterator for Tokens<R> {
+    type Item = (usize, Token);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_token() {
+            Ok(token) => token,
+            Err(e) => void::unreachable(e),
+        }
+    }
+}
+
+/// Writes tokens as a compressed stream, with a flag byte before every eight of them.
+///
+/// ```rust
+/// # use lzs::{Token, TokenWriteError, TokenWriter, VecWriter};
+/// # use void::ResultVoidExt;
+/// let mut writer = TokenWriter::new(VecWriter::with_capacity(30));
+/// for c in b"abc" {
+///     writer.write_token(Token::Literal(*c)).unwrap();
+/// }
+/// writer.write_token(Token::Match { pos: 0xFEE, len: 6 }).unwrap();
+/// let invalid = Token::Match { pos: 0xFEE, len: 19 };
+/// assert_eq!(
+///     writer.write_token(invalid),
+///     Err(TokenWriteError::Invalid(invalid))
+/// );
+/// let output = writer.finish().void_unwrap();
+/// assert_eq!(output, [0x07, b'a', b'b', b'c', 0xEE, 0xF3]);
+/// ```
+pub struct TokenWriter<W> {
+    writer: W,
+    code_buf: CodeBuf,
+}
+
+impl<W: Write> TokenWriter<W> {
+    /// Write the tokens into the writer.
+    #[inline]
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        TokenWriter {
+            writer,
+            code_buf: CodeBuf::new(),
+        }
+    }
+
+    /// Write a token.
+    ///
+    /// A match with a length outside of `THRESHOLD + 1..=F`, or a position of at least `N`, is
+    /// returned as [`Invalid`](TokenWriteError::Invalid) and nothing is written.
+    pub fn write_token(&mut self, token: Token) -> Result<(), TokenWriteError<W::Error>> {
+        match token {
+            Token::Literal(c) => self.code_buf.literal(c, &mut self.writer),
+            Token::Match { pos, len } => {
+                if pos >= Lzs::n() || len <= Lzs::threshold() || len > Lzs::f() {
+                    return Err(TokenWriteError::Invalid(token));
+                }
+                self.code_buf.pair(pos, len, &mut self.writer)
+            }
+        }
+        .map_err(TokenWriteError::Write)
+    }
+
+    /// Write the remaining tokens and convert the writer into its output.
+    pub fn finish(mut self) -> Result<W::Output, W::Error> {
+        self.code_buf.finish(&mut self.writer)?;
+        self.writer.finish()
+    }
+}
+
+/// Counts the read bytes.
+struct CountReader<'a, R> {
+    reader: &'a mut R,
+    count: &'a mut usize,
+}
+
+impl<R: Read> Read for CountReader<'_, R> {
+    type Error = R::Error;
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<u8>, Self::Error> {
+        let result = self.reader.read()?;
+        if result.is_some() {
+            *self.count += 1;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::token::{Token, TokenWriter, Tokens};
+    use crate::dynamic::Lzs;
+    use crate::error::TokenWriteError;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+    use void::ResultVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    #[test]
+    fn test_tokens() {
+        let big_test_data = include_bytes!("mod.rs");
+        let compressed = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let mut decompressed = 0;
+        let mut end = 0;
+        for (offset, token) in Tokens::new(SliceReader::new(&compressed)) {
+            // a flag byte before every eight tokens
+            assert!(offset == end || offset == end + 1);
+            end = offset + token.compressed_len();
+            if let Token::Literal(c) = token {
+                assert_eq!(c, big_test_data[decompressed]);
+            }
+            decompressed += token.decompressed_len();
+        }
+        assert_eq!(end, compressed.len());
+        assert_eq!(decompressed, big_test_data.len());
+    }
+
+    #[test]
+    fn test_tokens_truncated() {
+        // flags, literal, incomplete match
+        let mut tokens = Tokens::new(SliceReader::new(&[0xFD, b'a', 0x12]));
+        assert_eq!(tokens.next(), Some((1, Token::Literal(b'a'))));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(tokens.offset(), 3);
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_token_writer() {
+        let big_test_data = include_bytes!("mod.rs");
+        let compressed = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                VecWriter::with_capacity(big_test_data.len()),
+            )
+            .void_unwrap();
+        let mut writer = TokenWriter::new(VecWriter::with_capacity(compressed.len()));
+        for (_, token) in Tokens::new(SliceReader::new(&compressed)) {
+            writer.write_token(token).unwrap();
+        }
+        assert_eq!(writer.finish().void_unwrap(), compressed);
+    }
+
+    #[test]
+    fn test_token_writer_invalid() {
+        let mut writer = TokenWriter::new(VecWriter::with_capacity(10));
+        for token in [
+            Token::Match { pos: 0, len: 2 },
+            Token::Match { pos: 0, len: 19 },
+            Token::Match { pos: 4096, len: 3 },
+        ] {
+            assert_eq!(
+                writer.write_token(token),
+                Err(TokenWriteError::Invalid(token))
+            );
+        }
+        writer
+            .write_token(Token::Match { pos: 4095, len: 18 })
+            .unwrap();
+        assert_eq!(writer.finish().void_unwrap(), [0x00, 0xFF, 0xFF]);
+    }
+}
@@ -0,0 +1,161 @@
+/* This file is based on the LZSS encoder-decoder  (c) Haruhiko Okumura */
+
+use crate::dynamic::window::{compare, Finder};
+use crate::dynamic::Lzs;
+use crate::macros::{get, set};
+
+/// The binary search trees over all positions of the window.
+pub(crate) struct Tree {
+    lson: [u16; Lzs::n() + 1],
+    rson: [u16; Lzs::n() + 257],
+    dad: [u16; Lzs::n() + 1],
+}
+
+impl Tree {
+    #[inline(always)]
+    pub(crate) fn new() -> Self {
+        // Initialize trees with N
+        Tree {
+            lson: [Lzs::n() as u16; Lzs::n() + 1],
+            rson: [Lzs::n() as u16; Lzs::n() + 257],
+            dad: [Lzs::n() as u16; Lzs::n() + 1],
+        }
+    }
+
+    /**
+     * Inserts string of length F, `text_buf[r..r+F-1]`, into one of the
+     * trees (`text_buf[r]`'th tree) and returns the longest-match position
+     * and length.
+     * If `match_length` = F, then removes the old node in favor of the new
+     * one, because the old one will be deleted sooner.
+     * Note r plays double role, as tree node and position in buffer.
+     */
+    #[inline(always)]
+    fn insert_node(
+        &mut self,
+        r: usize,
+        text_buf: &[u8; Lzs::n() + Lzs::f() - 1],
+    ) -> (usize, usize) {
+        let Tree { lson, rson, dad } = self;
+        let mut match_position = 0;
+        let mut match_length = 0;
+
+        let mut cmp = 1i32;
+        let mut p = Lzs::n() + 1 + get!(text_buf, r) as usize;
+
+        set!(lson, r, Lzs::n() as u16);
+        set!(rson, r, Lzs::n() as u16);
+
+        loop {
+            if cmp >= 0 {
+                if get!(rson, p) == Lzs::n() as u16 {
+                    set!(rson, p, r as u16);
+                    set!(dad, r, p as u16);
+                    return (match_position, match_length);
+                }
+                p = get!(rson, p) as usize;
+            } else if get!(lson, p) == Lzs::n() as u16 {
+                set!(lson, p, r as u16);
+                set!(dad, r, p as u16);
+                return (match_position, match_length);
+            } else {
+                p = get!(lson, p) as usize;
+            }
+
+            let i;
+            (i, cmp) = compare(text_buf, r, p);
+
+            if i > match_length {
+                match_position = p;
+                match_length = i;
+
+                if match_length >= Lzs::f() {
+                    break;
+                }
+            }
+        }
+
+        set!(dad, r, get!(dad, p));
+        set!(lson, r, get!(lson, p));
+        set!(rson, r, get!(rson, p));
+
+        let e = get!(lson, p) as usize;
+        set!(dad, e, r as u16);
+        let e = get!(rson, p) as usize;
+        set!(dad, e, r as u16);
+
+        let e = get!(dad, p) as usize;
+        if get!(rson, e) == p as u16 {
+            set!(rson, e, r as u16);
+        } else {
+            set!(lson, e, r as u16);
+        }
+
+        set!(dad, p, Lzs::n() as u16); // Remove p
+
+        (match_position, match_length)
+    }
+
+    /**
+     * deletes node p from tree
+     */
+    #[inline(always)]
+    fn delete_node(&mut self, p: usize) {
+        let Tree { lson, rson, dad } = self;
+        if get!(dad, p) == Lzs::n() as u16 {
+            return; // Not in tree
+        }
+
+        let q = if get!(rson, p) == Lzs::n() as u16 {
+            get!(lson, p) as usize
+        } else if get!(lson, p) == Lzs::n() as u16 {
+            get!(rson, p) as usize
+        } else {
+            let mut q = get!(lson, p) as usize;
+            if get!(rson, q) != Lzs::n() as u16 {
+                loop {
+                    q = get!(rson, q) as usize;
+
+                    if get!(rson, q) == Lzs::n() as u16 {
+                        break;
+                    }
+                }
+                let e = get!(dad, q) as usize;
+                set!(rson, e, get!(lson, q));
+                let e = get!(lson, q) as usize;
+                set!(dad, e, get!(dad, q));
+                set!(lson, q, get!(lson, p));
+                let e = get!(lson, p) as usize;
+                set!(dad, e, q as u16);
+            }
+            set!(rson, q, get!(rson, p));
+            let e = get!(rson, p) as usize;
+            set!(dad, e, q as u16);
+            q
+        };
+
+        let e = get!(dad, p);
+        set!(dad, q, e);
+
+        let e = get!(dad, p) as usize;
+        if get!(rson, e) == p as u16 {
+            set!(rson, e, q as u16);
+        } else {
+            set!(lson, e, q as u16);
+        }
+
+        set!(dad, p, Lzs::n() as u16);
+    }
+}
+
+impl Finder for Tree {
+    #[inline(always)]
+    fn insert(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) -> (usize, usize) {
+        self.insert_node(r, text_buf)
+    }
+
+    #[inline(always)]
+    fn delete(&mut self, p: usize) {
+        self.delete_node(p);
+    }
+}
@@ -0,0 +1,174 @@
+/* This file is based on the LZSS encoder-decoder  (c) Haruhiko Okumura */
+
+use crate::dynamic::Lzs;
+use crate::macros::{get, get_u64, set};
+use crate::read_write::Read;
+
+/// Compare the `F` bytes at `r` and `p`, whose first bytes are equal.
+///
+/// Returns the length of the common prefix and the difference of the first differing bytes
+/// (zero if all are equal). Eight bytes are compared at once; `buf` has to contain `F - 1` bytes
+/// after `r` and `p`, like `text_buf`.
+#[inline(always)]
+pub(crate) fn compare(buf: &[u8], r: usize, p: usize) -> (usize, i32) {
+    let mut i = 1;
+    while i + 8 <= Lzs::f() {
+        let word_r = get_u64!(buf, r + i);
+        let word_p = get_u64!(buf, p + i);
+        let diff = word_r ^ word_p;
+        if diff != 0 {
+            let shift = diff.trailing_zeros() & !7;
+            return (
+                i + (shift / 8) as usize,
+                (word_r >> shift) as u8 as i32 - (word_p >> shift) as u8 as i32,
+            );
+        }
+        i += 8;
+    }
+    while i < Lzs::f() {
+        let cmp = get!(buf, r + i) as i32 - get!(buf, p + i) as i32;
+        if cmp != 0 {
+            return (i, cmp);
+        }
+        i += 1;
+    }
+    (Lzs::f(), 0)
+}
+
+/// Finds the longest match of a position in the window.
+pub(crate) trait Finder {
+    /// Add the position `r` and return the longest match (position and length) of
+    /// `text_buf[r..r+F-1]`.
+    fn insert(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) -> (usize, usize);
+
+    /// Add the position `r` when its match is not needed (because it is within a match).
+    #[inline(always)]
+    fn skip(&mut self, r: usize, text_buf: &[u8; Lzs::n() + Lzs::f() - 1]) {
+        self.insert(r, text_buf);
+    }
+
+    /// Remove the position `p` before it is overwritten.
+    fn delete(&mut self, p: usize);
+}
+
+/// The sliding window of the compressor.
+pub(crate) struct Window<F> {
+    /// The ring buffer, followed by a copy of its first `F - 1` bytes.
+    pub(crate) text_buf: [u8; Lzs::n() + Lzs::f() - 1],
+    finder: F,
+    /// Positions within a match are added if their offset is a multiple of it, none if zero.
+    every: usize,
+    /// The number of bytes at the start of the input which were already written, and are only
+    /// added to the window.
+    primed: usize,
+    /// The oldest position of the window, replaced by the next byte read.
+    s: usize,
+    /// The current position.
+    pub(crate) r: usize,
+    /// The number of bytes in the look-ahead buffer.
+    pub(crate) len: usize,
+}
+
+impl<F: Finder> Window<F> {
+    #[inline(always)]
+    pub(crate) fn new(c: u8, finder: F, every: usize, primed: usize) -> Self {
+        Window {
+            // Initialize the text_buf with C (a character that will appear often)
+            text_buf: [c; Lzs::n() + Lzs::f() - 1],
+            finder,
+            every,
+            primed,
+            s: 0,
+            r: Lzs::n() - Lzs::f(),
+            len: 0,
+        }
+    }
+
+    /// The byte at the current position.
+    #[inline(always)]
+    pub(crate) fn current(&self) -> u8 {
+        let text_buf = &self.text_buf;
+        get!(text_buf, self.r)
+    }
+
+    /// Fill the look-ahead buffer and return the longest match of the first position.
+    ///
+    /// Nothing is inserted if the input is empty. The primed bytes are moved over first, the
+    /// match is the one of the first byte after them.
+    #[inline(always)]
+    pub(crate) fn fill<R: Read>(&mut self, reader: &mut R) -> Result<(usize, usize), R::Error> {
+        while self.len < Lzs::f() {
+            if let Some(c) = reader.read()? {
+                let text_buf = &mut self.text_buf;
+                set!(text_buf, self.r + self.len, c);
+            } else {
+                break;
+            }
+
+            self.len += 1;
+        }
+
+        if self.len == 0 {
+            return Ok((0, 0));
+        }
+
+        for i in 1..=Lzs::f() {
+            self.finder.skip(self.r - i, &self.text_buf);
+        }
+
+        for _ in 0..self.primed {
+            self.finder.skip(self.r, &self.text_buf);
+            if !self.step(reader)? {
+                return Ok((0, 0));
+            }
+        }
+
+        Ok(self.finder.insert(self.r, &self.text_buf))
+    }
+
+    /// Move to the next position, reading one byte into the look-ahead buffer.
+    ///
+    /// Once the input is exhausted the look-ahead buffer shrinks instead. Returns `false` when
+    /// it became empty.
+    #[inline(always)]
+    fn step<R: Read>(&mut self, reader: &mut R) -> Result<bool, R::Error> {
+        let c = reader.read()?;
+        self.finder.delete(self.s);
+        if let Some(c) = c {
+            let text_buf = &mut self.text_buf;
+            set!(text_buf, self.s, c);
+
+            if self.s < Lzs::f() - 1 {
+                set!(text_buf, self.s + Lzs::n(), c);
+            }
+        } else {
+            self.len -= 1;
+        }
+
+        self.s = (self.s + 1) & (Lzs::n() - 1);
+        self.r = (self.r + 1) & (Lzs::n() - 1);
+
+        Ok(self.len > 0)
+    }
+
+    /// Move to the next position and return its longest match.
+    #[inline(always)]
+    pub(crate) fn advance<R: Read>(&mut self, reader: &mut R) -> Result<(usize, usize), R::Error> {
+        if self.step(reader)? {
+            Ok(self.finder.insert(self.r, &self.text_buf))
+        } else {
+            Ok((0, 0))
+        }
+    }
+
+    /// Move over `count` positions within a match, adding them according to the insertion.
+    #[inline(always)]
+    pub(crate) fn skip<R: Read>(&mut self, reader: &mut R, count: usize) -> Result<(), R::Error> {
+        for i in 1..=count {
+            if self.step(reader)? && (self.every == 1 || (self.every != 0 && i % self.every == 0)) {
+                self.finder.skip(self.r, &self.text_buf);
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use crate::dynamic::Token;
 use core::fmt::Display;
 
 /// This represents either an read or write error.
@@ -53,3 +54,210 @@ impl<R, W> LzsError<R, W> {
         }
     }
 }
+
+/// This represents an error while parsing a container around a compressed stream.
+///
+/// More variants may be added for new containers.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ContainerError {
+    /// The data is too short to contain the header.
+    MissingHeader,
+    /// The header declares more data than available.
+    Truncated {
+        /// The length declared in the header.
+        declared: usize,
+        /// The length which is actually available.
+        available: usize,
+    },
+    /// The index or the file list of an archive is malformed.
+    InvalidIndex,
+    /// The data uses an unsupported compression type.
+    UnsupportedCompression(u32),
+    /// The decompressed data has not the expected size.
+    SizeMismatch {
+        /// The size declared in the header or index.
+        expected: usize,
+        /// The size of the decompressed data.
+        actual: usize,
+    },
+    /// The header has not the expected signature.
+    InvalidSignature,
+    /// The checksum of the decompressed data does not match the header.
+    ChecksumMismatch {
+        /// The checksum declared in the header.
+        expected: u32,
+        /// The checksum of the decompressed data.
+        actual: u32,
+    },
+    /// A size or offset exceeds the 32-bit fields of the container.
+    TooLarge(usize),
+    /// The block size of a block container is zero.
+    ZeroBlockSize,
+}
+
+impl ContainerError {
+    /// Convert a size or offset into a 32-bit field.
+    #[inline]
+    pub(crate) fn u32_field(value: usize) -> Result<u32, ContainerError> {
+        u32::try_from(value).map_err(|_| ContainerError::TooLarge(value))
+    }
+}
+
+impl core::fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ContainerError::MissingHeader => f.write_str("Missing header"),
+            ContainerError::Truncated {
+                declared,
+                available,
+            } => write!(
+                f,
+                "Truncated data: {declared} bytes declared but only {available} available"
+            ),
+            ContainerError::InvalidIndex => f.write_str("Invalid index"),
+            ContainerError::UnsupportedCompression(compression) => {
+                write!(f, "Unsupported compression type {compression}")
+            }
+            ContainerError::SizeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Size mismatch: expected {expected} bytes but got {actual}"
+                )
+            }
+            ContainerError::InvalidSignature => f.write_str("Invalid signature"),
+            ContainerError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected 0x{expected:08x} but got 0x{actual:08x}"
+            ),
+            ContainerError::TooLarge(value) => {
+                write!(f, "Too large for the container: {value}")
+            }
+            ContainerError::ZeroBlockSize => f.write_str("Zero block size"),
+        }
+    }
+}
+
+/// Implementation of [`Error`](std::error::Error) for [`ContainerError`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl std::error::Error for ContainerError {}
+
+/// This represents an error while writing a container.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ContainerWriteError<E> {
+    /// The data doesn't fit the container.
+    Container(ContainerError),
+    /// The writer failed.
+    Write(E),
+}
+
+impl<E> From<ContainerError> for ContainerWriteError<E> {
+    #[inline]
+    fn from(error: ContainerError) -> Self {
+        ContainerWriteError::Container(error)
+    }
+}
+
+impl<E: Display> core::fmt::Display for ContainerWriteError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ContainerWriteError::Container(error) => error.fmt(f),
+            ContainerWriteError::Write(error) => write!(f, "Write error: {error}"),
+        }
+    }
+}
+
+/// Implementation of [`Error`](std::error::Error) for [`ContainerWriteError`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for ContainerWriteError<E> {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContainerWriteError::Container(error) => Some(error),
+            ContainerWriteError::Write(error) => Some(error),
+        }
+    }
+}
+
+/// The buffer of an in-place decompression is too short, the output would overwrite input
+/// which is not read yet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InPlaceError {
+    /// The minimal length of the buffer.
+    pub required: usize,
+    /// The length of the buffer.
+    pub available: usize,
+}
+
+impl core::fmt::Display for InPlaceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Buffer too short for in-place decompression: {} bytes required but only {} available",
+            self.required, self.available
+        )
+    }
+}
+
+/// Implementation of [`Error`](std::error::Error) for [`InPlaceError`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl std::error::Error for InPlaceError {}
+
+/// The compressed data doesn't fit the output of
+/// [`Lzs::compress_within`](crate::Lzs::compress_within) with any strategy.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BudgetExceeded {
+    /// The length of the smallest compressed data.
+    pub required: usize,
+    /// The length of the output.
+    pub available: usize,
+}
+
+impl core::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Compressed data exceeds the budget: {} bytes required but only {} available",
+            self.required, self.available
+        )
+    }
+}
+
+/// Implementation of [`Error`](std::error::Error) for [`BudgetExceeded`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl std::error::Error for BudgetExceeded {}
+
+/// This represents an error of [`TokenWriter::write_token`](crate::TokenWriter::write_token).
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TokenWriteError<W> {
+    /// The token can't be encoded, its length or position is out of range.
+    Invalid(Token),
+    /// Contains the write error value.
+    Write(W),
+}
+
+impl<W: Display> core::fmt::Display for TokenWriteError<W> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TokenWriteError::Invalid(token) => write!(f, "Invalid token: {token:?}"),
+            TokenWriteError::Write(error) => write!(f, "Write error: {error}"),
+        }
+    }
+}
+
+/// Implementation of [`Error`](std::error::Error) for [`TokenWriteError`]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[cfg(feature = "std")]
+impl<W: std::error::Error + 'static> std::error::Error for TokenWriteError<W> {
+    #[inline]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TokenWriteError::Invalid(_) => None,
+            TokenWriteError::Write(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,372 @@
+use crate::dynamic::Lzs;
+use crate::error::{ContainerError, ContainerWriteError, LzsError};
+use crate::length_prefixed::LengthPrefixed;
+use crate::slice::SliceReader;
+use crate::vec::VecWriter;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use void::ResultVoidExt;
+
+/// The compression of an entry in a [`Ff8Archive`](crate::Ff8Archive).
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Ff8Compression {
+    /// The entry is stored as is.
+    None,
+    /// The entry is a [`LengthPrefixed`](crate::LengthPrefixed) LZS stream.
+    Lzs,
+    /// The entry is a LZ4 stream (only used by the remastered version, can't be decompressed).
+    Lz4,
+}
+
+impl Ff8Compression {
+    fn from_index(value: u32) -> Result<Ff8Compression, ContainerError> {
+        match value {
+            0 => Ok(Ff8Compression::None),
+            1 => Ok(Ff8Compression::Lzs),
+            2 => Ok(Ff8Compression::Lz4),
+            _ => Err(ContainerError::UnsupportedCompression(value)),
+        }
+    }
+
+    fn to_index(self) -> u32 {
+        match self {
+            Ff8Compression::None => 0,
+            Ff8Compression::Lzs => 1,
+            Ff8Compression::Lz4 => 2,
+        }
+    }
+}
+
+/// An entry of a [`Ff8Archive`](crate::Ff8Archive).
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Ff8Entry<'a> {
+    name: &'a str,
+    size: usize,
+    offset: usize,
+    compression: Ff8Compression,
+}
+
+impl<'a> Ff8Entry<'a> {
+    /// The path of the entry, as stored in the file list (e.g. `c:\ff8\data\eng\field\...`).
+    #[inline]
+    #[must_use]
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The uncompressed size.
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The offset of the stored data in the data file.
+    #[inline]
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The compression of the stored data.
+    #[inline]
+    #[must_use]
+    pub fn compression(&self) -> Ff8Compression {
+        self.compression
+    }
+}
+
+/// Read a Final Fantasy VIII archive.
+///
+/// An archive consists of three files:
+/// * `.fi` - The index, with the uncompressed size, offset and compression of each entry
+///           (three 32-bit little-endian values).
+/// * `.fl` - The file list, with the path of each entry on a separate line.
+/// * `.fs` - The data.
+///
+/// ```rust
+/// # use lzs::{Ff8Archive, Ff8ArchiveWriter, Ff8Compression, Lzs};
+/// let lzs = Lzs::new(0x00);
+/// let mut writer = Ff8ArchiveWriter::new(lzs);
+/// writer.add(r"c:\ff8\data\eng\field\test.msd", b"Example Data", Ff8Compression::Lzs)
+///     .unwrap();
+/// let (index, file_list, data) = writer.finish();
+///
+/// let archive = Ff8Archive::open(&index, &file_list, &data).unwrap();
+/// let entry = archive.find(r"C:\FF8\DATA\ENG\FIELD\TEST.MSD").unwrap();
+/// assert_eq!(entry.size(), 12);
+/// assert_eq!(archive.extract(entry, lzs).unwrap(), b"Example Data");
+/// ```
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+pub struct Ff8Archive<'a> {
+    entries: Vec<Ff8Entry<'a>>,
+    data: &'a [u8],
+}
+
+impl<'a> Ff8Archive<'a> {
+    /// The size of an entry in the index.
+    pub const INDEX_ENTRY_LEN: usize = 12;
+
+    /// Open an archive from the contents of the index, the file list and the data.
+    pub fn open(
+        index: &'a [u8],
+        file_list: &'a [u8],
+        data: &'a [u8],
+    ) -> Result<Ff8Archive<'a>, ContainerError> {
+        if !index.len().is_multiple_of(Self::INDEX_ENTRY_LEN) {
+            return Err(ContainerError::InvalidIndex);
+        }
+        let file_list =
+            core::str::from_utf8(file_list).map_err(|_| ContainerError::InvalidIndex)?;
+        let mut names = file_list.lines();
+        let mut entries = Vec::with_capacity(index.len() / Self::INDEX_ENTRY_LEN);
+        for values in index.chunks_exact(Self::INDEX_ENTRY_LEN) {
+            let value = |i: usize| {
+                u32::from_le_bytes([values[i], values[i + 1], values[i + 2], values[i + 3]])
+            };
+            entries.push(Ff8Entry {
+                name: names.next().ok_or(ContainerError::InvalidIndex)?,
+                size: value(0) as usize,
+                offset: value(4) as usize,
+                compression: Ff8Compression::from_index(value(8))?,
+            });
+        }
+        if names.any(|name| !name.is_empty()) {
+            return Err(ContainerError::InvalidIndex);
+        }
+        Ok(Ff8Archive { entries, data })
+    }
+
+    /// All entries, in the order of the index.
+    #[inline]
+    #[must_use]
+    pub fn entries(&self) -> &[Ff8Entry<'a>] {
+        &self.entries
+    }
+
+    /// Find an entry by its path, ignoring ASCII case.
+    #[must_use]
+    pub fn find(&self, name: &str) -> Option<&Ff8Entry<'a>> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The stored data of an entry, including the length prefix of compressed entries.
+    pub fn raw(&self, entry: &Ff8Entry<'a>) -> Result<&'a [u8], ContainerError> {
+        let available = self.data.len().saturating_sub(entry.offset);
+        let len = match entry.compression {
+            Ff8Compression::None => entry.size,
+            Ff8Compression::Lzs | Ff8Compression::Lz4 => {
+                let data = self.data.get(entry.offset..).unwrap_or_default();
+                LengthPrefixed::HEADER_LEN + LengthPrefixed::parse(data)?.declared_len()
+            }
+        };
+        entry
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(entry.offset..end))
+            .ok_or(ContainerError::Truncated {
+                declared: len,
+                available,
+            })
+    }
+
+    /// Extract an entry, LZS compressed entries are decompressed with the given parameters.
+    pub fn extract(&self, entry: &Ff8Entry<'a>, lzs: Lzs) -> Result<Vec<u8>, ContainerError> {
+        let raw = self.raw(entry)?;
+        let content = match entry.compression {
+            Ff8Compression::None => raw.to_vec(),
+            Ff8Compression::Lzs => LengthPrefixed::parse(raw)?
+                .decompress(lzs, VecWriter::with_capacity(entry.size))
+                .void_unwrap(),
+            Ff8Compression::Lz4 => {
+                return Err(ContainerError::UnsupportedCompression(
+                    entry.compression.to_index(),
+                ))
+            }
+        };
+        if content.len() == entry.size {
+            Ok(content)
+        } else {
+            Err(ContainerError::SizeMismatch {
+                expected: entry.size,
+                actual: content.len(),
+            })
+        }
+    }
+}
+
+/// Create a Final Fantasy VIII archive, see [`Ff8Archive`](crate::Ff8Archive) for the format.
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+pub struct Ff8ArchiveWriter {
+    lzs: Lzs,
+    index: Vec<u8>,
+    file_list: Vec<u8>,
+    data: Vec<u8>,
+}
+
+impl Ff8ArchiveWriter {
+    /// Constructs a new writer, LZS compressed entries are compressed with the given parameters.
+    #[must_use]
+    pub fn new(lzs: Lzs) -> Ff8ArchiveWriter {
+        Ff8ArchiveWriter {
+            lzs,
+            index: Vec::new(),
+            file_list: Vec::new(),
+            data: Vec::new(),
+        }
+    }
+
+    /// Add an entry, the content is compressed if requested.
+    ///
+    /// LZ4 compression is not supported. An entry or archive of 4 GiB or more is returned as
+    /// [`ContainerError::TooLarge`] and nothing is added.
+    pub fn add(
+        &mut self,
+        name: &str,
+        content: &[u8],
+        compression: Ff8Compression,
+    ) -> Result<(), ContainerError> {
+        let fields = self.fields(content.len())?;
+        match compression {
+            Ff8Compression::None => self.data.extend_from_slice(content),
+            Ff8Compression::Lzs => {
+                let compressed = match self.lzs.compress_length_prefixed(
+                    SliceReader::new(content),
+                    VecWriter::with_capacity(content.len()),
+                ) {
+                    Ok(compressed) => compressed,
+                    Err(LzsError::WriteError(ContainerWriteError::Container(error))) => {
+                        return Err(error)
+                    }
+                    Err(
+                        LzsError::ReadError(void)
+                        | LzsError::WriteError(ContainerWriteError::Write(void)),
+                    ) => void::unreachable(void),
+                };
+                self.data.extend_from_slice(&compressed);
+            }
+            Ff8Compression::Lz4 => {
+                return Err(ContainerError::UnsupportedCompression(
+                    compression.to_index(),
+                ))
+            }
+        }
+        self.push_entry(name, fields, compression);
+        Ok(())
+    }
+
+    /// Add an entry with already stored data (see [`Ff8Archive::raw`](crate::Ff8Archive::raw)),
+    /// for example to copy it from another archive without recompression.
+    ///
+    /// An entry or archive of 4 GiB or more is returned as [`ContainerError::TooLarge`] and
+    /// nothing is added.
+    pub fn add_raw(&mut self, entry: &Ff8Entry<'_>, raw: &[u8]) -> Result<(), ContainerError> {
+        let fields = self.fields(entry.size)?;
+        self.data.extend_from_slice(raw);
+        self.push_entry(entry.name, fields, entry.compression);
+        Ok(())
+    }
+
+    /// The size and the offset of an entry added at the end, which have to fit in 32 bits.
+    fn fields(&self, size: usize) -> Result<[u32; 2], ContainerError> {
+        Ok([
+            ContainerError::u32_field(size)?,
+            ContainerError::u32_field(self.data.len())?,
+        ])
+    }
+
+    fn push_entry(&mut self, name: &str, [size, offset]: [u32; 2], compression: Ff8Compression) {
+        for value in [size, offset, compression.to_index()] {
+            self.index.extend_from_slice(&value.to_le_bytes());
+        }
+        self.file_list.extend_from_slice(name.as_bytes());
+        self.file_list.extend_from_slice(b"\r\n");
+    }
+
+    /// Returns the index, the file list and the data.
+    #[must_use]
+    pub fn finish(self) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        (self.index, self.file_list, self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::error::ContainerError;
+    use crate::ff8::{Ff8Archive, Ff8ArchiveWriter, Ff8Compression};
+
+    const TEST_LZS: Lzs = Lzs::new(0x00);
+    const TEST_DATA: &[u8; 27] = b"Sample   Data   11221233123";
+
+    #[test]
+    fn test_roundtrip() {
+        let mut writer = Ff8ArchiveWriter::new(TEST_LZS);
+        writer
+            .add(r"c:\ff8\data\a.bin", TEST_DATA, Ff8Compression::Lzs)
+            .unwrap();
+        writer
+            .add(r"c:\ff8\data\b.bin", TEST_DATA, Ff8Compression::None)
+            .unwrap();
+        assert_eq!(
+            writer.add("c", TEST_DATA, Ff8Compression::Lz4),
+            Err(ContainerError::UnsupportedCompression(2))
+        );
+        let (index, file_list, data) = writer.finish();
+        assert_eq!(
+            file_list,
+            b"c:\\ff8\\data\\a.bin\r\nc:\\ff8\\data\\b.bin\r\n"
+        );
+
+        let archive = Ff8Archive::open(&index, &file_list, &data).unwrap();
+        assert_eq!(archive.entries().len(), 2);
+        for entry in archive.entries() {
+            assert_eq!(archive.extract(entry, TEST_LZS).unwrap(), TEST_DATA);
+        }
+        let entry = archive.entries()[1];
+        let first = archive.raw(&archive.entries()[0]).unwrap();
+        assert_eq!(entry.offset(), first.len());
+        assert_eq!(archive.raw(&entry).unwrap(), TEST_DATA);
+
+        let mut writer = Ff8ArchiveWriter::new(TEST_LZS);
+        for entry in archive.entries() {
+            writer.add_raw(entry, archive.raw(entry).unwrap()).unwrap();
+        }
+        assert_eq!(writer.finish(), (index.clone(), file_list.clone(), data));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let index = [27, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            Ff8Archive::open(&index[..8], b"a\r\n", TEST_DATA).err(),
+            Some(ContainerError::InvalidIndex)
+        );
+        assert_eq!(
+            Ff8Archive::open(&index, b"a\r\nb\r\n", TEST_DATA).err(),
+            Some(ContainerError::InvalidIndex)
+        );
+        let archive = Ff8Archive::open(&index, b"a\r\n", &TEST_DATA[..20]).unwrap();
+        assert_eq!(
+            archive.extract(&archive.entries()[0], TEST_LZS),
+            Err(ContainerError::Truncated {
+                declared: 27,
+                available: 20
+            })
+        );
+        // an empty entry after the end of the data
+        let index = [0, 0, 0, 0, 100, 0, 0, 0, 0, 0, 0, 0];
+        let archive = Ff8Archive::open(&index, b"a\r\n", TEST_DATA).unwrap();
+        assert_eq!(
+            archive.raw(&archive.entries()[0]),
+            Err(ContainerError::Truncated {
+                declared: 0,
+                available: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::ContainerError;
+#[cfg(feature = "alloc")]
+use crate::{
+    dynamic::Lzs,
+    error::{ContainerWriteError, LzsError},
+    read_write::{Read, Write},
+    slice::SliceReader,
+    vec::VecWriter,
+    void::{ResultLzsErrorVoidReadExt, ResultLzsErrorVoidWriteExt},
+};
+
+/// A compressed stream with a 32-bit little-endian length prefix.
+///
+/// This is the container used by Final Fantasy VII and VIII for field, world map and battle
+/// files: four bytes with the length of the compressed stream, followed by the stream itself.
+///
+/// Not all original files match the declared length exactly. Some contain additional bytes
+/// after the stream (see [`trailing`](LengthPrefixed::trailing)), others declare a few bytes
+/// more than the file contains (see [`parse_lenient`](LengthPrefixed::parse_lenient)).
+///
+/// ```rust
+/// # use lzs::{LengthPrefixed, Lzs, SliceReader, VecWriter};
+/// let lzs = Lzs::new(0x20);
+/// let file = lzs
+///     .compress_length_prefixed(SliceReader::new(b"Example Data"), VecWriter::with_capacity(30))
+///     .unwrap();
+/// assert_eq!(&file[..4], &[14, 0, 0, 0]);
+///
+/// let container = LengthPrefixed::parse(&file).unwrap();
+/// assert_eq!(container.declared_len(), 14);
+/// assert!(container.trailing().is_empty());
+/// let data = container.decompress(lzs, VecWriter::with_capacity(12));
+/// assert_eq!(data.unwrap(), b"Example Data");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct LengthPrefixed<'a> {
+    declared: usize,
+    stream: &'a [u8],
+    trailing: &'a [u8],
+}
+
+impl<'a> LengthPrefixed<'a> {
+    /// The length of the header.
+    pub const HEADER_LEN: usize = 4;
+
+    /// Parse the container.
+    ///
+    /// Additional bytes after the stream are accepted, but an error is returned if the header
+    /// declares more bytes than available.
+    pub fn parse(data: &'a [u8]) -> Result<LengthPrefixed<'a>, ContainerError> {
+        let container = Self::parse_lenient(data)?;
+        if container.missing() > 0 {
+            Err(ContainerError::Truncated {
+                declared: container.declared,
+                available: container.stream.len(),
+            })
+        } else {
+            Ok(container)
+        }
+    }
+
+    /// Parse the container, but accept a declared length which exceeds the available data.
+    ///
+    /// The stream is then cut at the end of the data, see [`missing`](LengthPrefixed::missing).
+    pub fn parse_lenient(data: &'a [u8]) -> Result<LengthPrefixed<'a>, ContainerError> {
+        if data.len() < Self::HEADER_LEN {
+            return Err(ContainerError::MissingHeader);
+        }
+        let (header, rest) = data.split_at(Self::HEADER_LEN);
+        let declared = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let (stream, trailing) = rest.split_at(declared.min(rest.len()));
+        Ok(LengthPrefixed {
+            declared,
+            stream,
+            trailing,
+        })
+    }
+
+    /// The length of the stream as declared in the header.
+    #[inline]
+    #[must_use]
+    pub fn declared_len(&self) -> usize {
+        self.declared
+    }
+
+    /// The compressed stream (without the header).
+    #[inline]
+    #[must_use]
+    pub fn stream(&self) -> &'a [u8] {
+        self.stream
+    }
+
+    /// The bytes after the declared stream, usually padding.
+    #[inline]
+    #[must_use]
+    pub fn trailing(&self) -> &'a [u8] {
+        self.trailing
+    }
+
+    /// The amount of bytes declared but not available.
+    ///
+    /// This is always zero for a container created by [`parse`](LengthPrefixed::parse).
+    #[inline]
+    #[must_use]
+    pub fn missing(&self) -> usize {
+        self.declared - self.stream.len()
+    }
+
+    /// Whether the declared length matches the available data exactly.
+    #[inline]
+    #[must_use]
+    pub fn is_exact(&self) -> bool {
+        self.missing() == 0 && self.trailing.is_empty()
+    }
+
+    /// Decompress the stream into the output.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[cfg(feature = "alloc")]
+    pub fn decompress<W: Write>(&self, lzs: Lzs, writer: W) -> Result<W::Output, W::Error> {
+        lzs.decompress(SliceReader::new(self.stream), writer)
+            .void_read_unwrap()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Lzs {
+    /// Compress the input data into the output, with a 32-bit little-endian length prefix.
+    ///
+    /// See [`LengthPrefixed`](crate::LengthPrefixed) for the format, the compressed data is
+    /// collected on the heap in order to write the header first. Compressed data of 4 GiB or
+    /// more is returned as [`ContainerError::TooLarge`] and nothing is written.
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    #[allow(clippy::type_complexity)]
+    pub fn compress_length_prefixed<R: Read, W: Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+    ) -> Result<W::Output, LzsError<R::Error, ContainerWriteError<W::Error>>> {
+        let data = self
+            .compress(reader, VecWriter::with_capacity(0))
+            .void_write_unwrap()
+            .map_err(LzsError::ReadError)?;
+        let declared = ContainerError::u32_field(data.len())
+            .map_err(|error| LzsError::WriteError(error.into()))?;
+        for c in declared.to_le_bytes().iter().chain(data.iter()) {
+            writer
+                .write(*c)
+                .map_err(|error| LzsError::WriteError(ContainerWriteError::Write(error)))?;
+        }
+        writer
+            .finish()
+            .map_err(|error| LzsError::WriteError(ContainerWriteError::Write(error)))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::error::ContainerError;
+    use crate::length_prefixed::LengthPrefixed;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+    const TEST_DATA: &[u8; 27] = b"Sample   Data   11221233123";
+
+    fn compressed() -> Vec<u8> {
+        TEST_LZS
+            .compress_length_prefixed(SliceReader::new(TEST_DATA), VecWriter::with_capacity(31))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let data = compressed();
+        assert_eq!(&data[..4], &[27, 0, 0, 0]);
+        let container = LengthPrefixed::parse(&data).unwrap();
+        assert!(container.is_exact());
+        let output = container
+            .decompress(TEST_LZS, VecWriter::with_capacity(TEST_DATA.len()))
+            .unwrap();
+        assert_eq!(output.as_slice(), TEST_DATA);
+    }
+
+    #[test]
+    fn test_trailing() {
+        let mut data = compressed();
+        data.extend_from_slice(&[0, 0, 0]);
+        let container = LengthPrefixed::parse(&data).unwrap();
+        assert!(!container.is_exact());
+        assert_eq!(container.trailing(), &[0, 0, 0]);
+        let output = container
+            .decompress(TEST_LZS, VecWriter::with_capacity(TEST_DATA.len()))
+            .unwrap();
+        assert_eq!(output.as_slice(), TEST_DATA);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut data = compressed();
+        data.truncate(data.len() - 2);
+        assert_eq!(
+            LengthPrefixed::parse(&data),
+            Err(ContainerError::Truncated {
+                declared: 27,
+                available: 25
+            })
+        );
+        let container = LengthPrefixed::parse_lenient(&data).unwrap();
+        assert_eq!(container.missing(), 2);
+        assert_eq!(
+            LengthPrefixed::parse(&data[..3]),
+            Err(ContainerError::MissingHeader)
+        );
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_too_large() {
+        assert_eq!(ContainerError::u32_field(0xFFFF_FFFF), Ok(0xFFFF_FFFF));
+        assert_eq!(
+            ContainerError::u32_field(1 << 32),
+            Err(ContainerError::TooLarge(1 << 32))
+        );
+    }
+}
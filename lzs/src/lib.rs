@@ -40,6 +40,9 @@
 //! possible to check if the contents is correct, or even the length matches.
 //! It is recommended to add a header based on the requirements.
 //!
+//! The 32-bit length prefix used by Final Fantasy VII and VIII is supported with
+//! [`LengthPrefixed`](crate::LengthPrefixed).
+//!
 //! # Origin
 //! This code is based on the [LZSS encoder-decoder by Haruhiko Okumura, public domain](http://oak.oakland.edu:80/pub/simtelnet/msdos/arcutils/lz_comp2.zip).
 //!
@@ -48,6 +51,8 @@
 //!
 //! # Features
 //! * `alloc`       - Allows de-/compression with buffer on the heap and the [`VecWriter`](crate::VecWriter).
+//! * `parallel`    - Enables `std` and the compression of blocks on multiple threads, like
+//!                   `compress_blocks_parallel`.
 //! * `safe`        - Only use safe code (see Safety below).
 //! * `std`         - Enables `alloc` and additional [`IOSimpleReader`](crate::IOSimpleReader), [`IOSimpleWriter`](crate::IOSimpleWriter),
 //!                   and the [`Error`](::std::error::Error) instance for [`LzsError`](crate::LzsError).
@@ -87,26 +92,56 @@
 //! But on smaller systems (like microcontrollers, where `no_std` is needed) it may be noticeable.
 //! Which is the reason wht it can be switched on/off.
 
-pub use crate::dynamic::Lzs;
-pub use crate::error::LzsError;
+pub use crate::block::Blocks;
+#[cfg(feature = "std")]
+pub use crate::checkpoint::CheckpointReader;
+#[cfg(feature = "alloc")]
+pub use crate::checkpoint::{Checkpoint, CheckpointIndex};
+pub use crate::complzss::Complzss;
+#[cfg(feature = "alloc")]
+pub use crate::detect::{Detection, Dialect, LengthHeader};
+pub use crate::dynamic::{Compat, Insertion, Lzs, MatchFinder, Parser, Token, TokenWriter, Tokens};
+#[cfg(feature = "alloc")]
+pub use crate::dynamic::{Recovered, RecoveredRange};
+pub use crate::error::{
+    BudgetExceeded, ContainerError, ContainerWriteError, InPlaceError, LzsError, TokenWriteError,
+};
+#[cfg(feature = "alloc")]
+pub use crate::ff8::{Ff8Archive, Ff8ArchiveWriter, Ff8Compression, Ff8Entry};
 #[cfg(feature = "std")]
 pub use crate::io_simple::{IOSimpleReader, IOSimpleWriter};
+pub use crate::length_prefixed::LengthPrefixed;
 pub use crate::read_write::{Read, Write};
+#[cfg(feature = "alloc")]
+pub use crate::scan::{Hit, Scanner};
 pub use crate::slice::{SliceReader, SliceWriteError, SliceWriter, SliceWriterExact};
+pub use crate::stats::{Stats, StatsWriter};
 #[cfg(feature = "alloc")]
 pub use crate::vec::VecWriter;
 pub use crate::void::{
     ResultLzsErrorVoidExt, ResultLzsErrorVoidReadExt, ResultLzsErrorVoidWriteExt,
 };
 
+mod block;
+#[cfg(feature = "alloc")]
+mod checkpoint;
+mod complzss;
+#[cfg(feature = "alloc")]
+mod detect;
 mod dynamic;
 mod error;
+#[cfg(feature = "alloc")]
+mod ff8;
 #[cfg(feature = "std")]
 mod io_simple;
+mod length_prefixed;
 mod macros;
 mod read_write;
+#[cfg(feature = "alloc")]
+mod scan;
 #[cfg_attr(feature = "safe", path = "slice_safe.rs")]
 mod slice;
+mod stats;
 #[cfg(feature = "alloc")]
 mod vec;
 mod void;
@@ -11,6 +11,15 @@ macro_rules! set {
     };
 }
 
+#[cfg(not(feature = "safe"))]
+macro_rules! get_u64 {
+    ($slice:ident,$offset:expr) => {
+        u64::from_le(unsafe {
+            core::ptr::read_unaligned($slice.get_unchecked($offset..).as_ptr().cast::<u64>())
+        })
+    };
+}
+
 #[cfg(feature = "safe")]
 macro_rules! get {
     ($slice:ident,$offset:expr) => {
@@ -24,5 +33,14 @@ macro_rules! set {
     };
 }
 
+#[cfg(feature = "safe")]
+macro_rules! get_u64 {
+    ($slice:ident,$offset:expr) => {{
+        let offset = $offset;
+        u64::from_le_bytes($slice[offset..offset + 8].try_into().unwrap())
+    }};
+}
+
 pub(crate) use get;
+pub(crate) use get_u64;
 pub(crate) use set;
@@ -0,0 +1,346 @@
+use crate::detect::{Decoder, Detection, Dialect, LengthHeader};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// The number of offsets after the start of a stream without header which are compared.
+const SHIFT: usize = 2048;
+/// The number of bytes decoded to compare the starts.
+const COMPARED: usize = 2048;
+/// The length of a run of the same byte which is taken as padding between streams.
+const RUN: usize = 16;
+
+/// A compressed stream found by a [`Scanner`].
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hit {
+    /// The offset of the header, or of the stream without header.
+    pub offset: usize,
+    /// The compressed stream, without header.
+    pub stream: Range<usize>,
+    /// The result of the stream, with the layout including the header and the confidence score.
+    pub detection: Detection,
+}
+
+impl Hit {
+    /// The header and the stream.
+    #[inline]
+    #[must_use]
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.stream.end
+    }
+
+    /// Decompress the stream, if the layout is supported by [`Lzs`](crate::Lzs).
+    ///
+    /// The output is cut at the decompressed length declared by the header.
+    #[must_use]
+    pub fn decompress(&self, data: &[u8]) -> Option<Vec<u8>> {
+        let mut output = self
+            .detection
+            .lzs()?
+            .decompress_vec(&data[self.stream.clone()]);
+        if self.detection.dialect.header() == LengthHeader::Decompressed {
+            output.truncate(self.detection.decompressed);
+        }
+        Some(output)
+    }
+}
+
+/// Finds compressed streams at unknown offsets of larger data, like executables and disc images.
+///
+/// Each offset is tried as the start of a stream with a 32-bit little-endian header of the
+/// compressed or decompressed length, see [`LengthHeader`]. The stream has to decode to the
+/// declared length without invalid matches. Then the offsets between them are tried as the
+/// start of a stream without header, which ends before the flag byte of the first invalid
+/// match, so it may include some bytes after the actual end. The decoded streams are scored
+/// like in [`Dialect::detect`], the hits with a lower score or shorter output are dropped.
+///
+/// ```rust
+/// # use lzs::{Lzs, Scanner, SliceReader, VecWriter};
+/// let data: Vec<u8> = (0..200u32)
+///     .flat_map(|i| format!("line {}: {}\n", i, i * i % 97).into_bytes())
+///     .collect();
+/// let stream = Lzs::new(0x20)
+///     .compress_length_prefixed(SliceReader::new(&data), VecWriter::with_capacity(0))
+///     .unwrap();
+/// let mut file = vec![0xFF; 1000];
+/// file.extend_from_slice(&stream);
+/// file.extend_from_slice(&[0xFF; 1000]);
+///
+/// let hits = Scanner::new().scan(&file);
+/// assert_eq!(hits.len(), 1);
+/// assert_eq!(hits[0].range(), 1000..1000 + stream.len());
+/// assert_eq!(hits[0].decompress(&file).unwrap(), data);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Scanner {
+    dialect: Dialect,
+    min_decompressed: usize,
+    min_score: u32,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner {
+    /// Scan for streams of this crate, see [`Dialect::LZS`].
+    #[must_use]
+    pub const fn new() -> Self {
+        Scanner {
+            dialect: Dialect::LZS,
+            min_decompressed: 64,
+            min_score: 85,
+        }
+    }
+
+    /// Scan for streams of another layout, its header is ignored.
+    #[must_use]
+    pub const fn with_dialect(self, dialect: Dialect) -> Self {
+        Scanner { dialect, ..self }
+    }
+
+    /// Drop the streams which decompress to fewer bytes, 64 by default.
+    #[must_use]
+    pub const fn with_min_decompressed(self, min_decompressed: usize) -> Self {
+        Scanner {
+            min_decompressed,
+            ..self
+        }
+    }
+
+    /// Drop the streams with a lower score, 85 by default.
+    #[must_use]
+    pub const fn with_min_score(self, min_score: u32) -> Self {
+        Scanner { min_score, ..self }
+    }
+
+    /// Find the streams in the data, ordered by offset.
+    #[must_use]
+    pub fn scan(&self, data: &[u8]) -> Vec<Hit> {
+        let mut decoder = Decoder::new(self.dialect.with_header(LengthHeader::None));
+        let mut hits = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            if let Some(hit) = self.prefixed(&mut decoder, data, offset) {
+                offset = hit.stream.end;
+                hits.push(hit);
+            } else {
+                offset += 1;
+            }
+        }
+
+        // Streams without header are searched between the prefixed streams and padding.
+        let mut skipped: Vec<_> = hits.iter().map(Hit::range).chain(runs(data)).collect();
+        skipped.sort_by_key(|range| range.start);
+        let mut unprefixed = Vec::new();
+        let mut start = 0;
+        skipped.push(data.len()..data.len());
+        for skip in skipped {
+            offset = start;
+            while offset < skip.start {
+                if let Some(hit) = self.unprefixed(&mut decoder, &data[..skip.start], offset) {
+                    offset = hit.stream.end;
+                    unprefixed.push(hit);
+                } else {
+                    offset += 1;
+                }
+            }
+            start = start.max(skip.end);
+        }
+        hits.append(&mut unprefixed);
+        hits.sort_by_key(|hit| hit.offset);
+        hits
+    }
+
+    /// Keep the stream if it is long and likely enough.
+    fn hit(&self, offset: usize, stream: Range<usize>, detection: Detection) -> Option<Hit> {
+        (detection.decompressed >= self.min_decompressed && detection.score >= self.min_score)
+            .then_some(Hit {
+                offset,
+                stream,
+                detection,
+            })
+    }
+
+    /// Try a stream with a length header at the offset.
+    fn prefixed(&self, decoder: &mut Decoder, data: &[u8], offset: usize) -> Option<Hit> {
+        let (header, rest) = data[offset..].split_first_chunk::<4>()?;
+        let declared = u32::from_le_bytes(*header) as usize;
+        let start = offset + 4;
+
+        if declared > 1 && declared <= rest.len() {
+            let stream = &rest[..declared];
+            decoder.decode(stream, None, true);
+            if decoder.end == declared && decoder.output.len() >= self.min_decompressed {
+                let mut detection = Detection::of(decoder, declared);
+                detection.dialect = detection.dialect.with_header(LengthHeader::Compressed);
+                if let Some(hit) = self.hit(offset, start..start + declared, detection) {
+                    return Some(hit);
+                }
+            }
+        }
+
+        // A match decompresses to at most F bytes from two bytes and a bit.
+        if declared >= self.min_decompressed && declared <= rest.len() * self.dialect.f() / 2 {
+            decoder.decode(rest, Some(declared), true);
+            if decoder.output.len() == declared {
+                let end = decoder.end;
+                let mut detection = Detection::of(decoder, end);
+                detection.dialect = detection.dialect.with_header(LengthHeader::Decompressed);
+                return self.hit(offset, start..start + end, detection);
+            }
+        }
+        None
+    }
+
+    /// Try a stream without header at the offset, or a later one which is more likely.
+    ///
+    /// The bytes before a stream often decode without invalid matches until they meet it, so
+    /// the next offsets are compared by the rating of their first bytes, less if they end.
+    fn unprefixed(&self, decoder: &mut Decoder, data: &[u8], offset: usize) -> Option<Hit> {
+        let first = self.decode(decoder, data, offset, self.min_score)?;
+        let first = self.hit(offset, first.stream, first.detection)?;
+        let mut best = (0, offset);
+        for start in offset..first.stream.end.min(offset + SHIFT) {
+            let compared = &data[..data.len().min(start + COMPARED)];
+            let min_score = (best.0 / COMPARED / 10) as u32;
+            if let Some(candidate) = self.decode(decoder, compared, start, min_score) {
+                let weight = candidate.detection.rating() as usize * candidate.stream.len();
+                if weight > best.0 {
+                    best = (weight, start);
+                }
+            }
+        }
+        if best.1 > offset {
+            if let Some(better) = self.decode(decoder, data, best.1, self.min_score) {
+                if let Some(better) = self.hit(best.1, better.stream, better.detection) {
+                    return Some(better);
+                }
+            }
+        }
+        Some(first)
+    }
+
+    /// Decode a stream without header until the first invalid match.
+    ///
+    /// The score and decompressed length are not checked, but the streams which can't reach
+    /// `min_score` are skipped early.
+    fn decode(
+        &self,
+        decoder: &mut Decoder,
+        data: &[u8],
+        offset: usize,
+        min_score: u32,
+    ) -> Option<Hit> {
+        let walked = Detection::walk(self.dialect, &data[offset..]);
+        if walked.decompressed < self.min_decompressed || walked.score < min_score {
+            return None;
+        }
+        decoder.decode(&data[offset..], None, true);
+        // Decode again without the units after the end.
+        let end = decoder.end;
+        if end == 0 {
+            return None;
+        }
+        decoder.decode(&data[offset..offset + end], None, false);
+        Some(Hit {
+            offset,
+            stream: offset..offset + end,
+            detection: Detection::of(decoder, end),
+        })
+    }
+}
+
+/// The runs of at least [`RUN`] same bytes, without their last byte, which may be the first
+/// flag byte of a stream.
+fn runs(data: &[u8]) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for end in 1..=data.len() {
+        if end == data.len() || data[end] != data[start] {
+            if end - start >= RUN {
+                runs.push(start..end - 1);
+            }
+            start = end;
+        }
+    }
+    runs
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::detect::LengthHeader;
+    use crate::dynamic::Lzs;
+    use crate::scan::Scanner;
+    use crate::slice::SliceReader;
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    fn compress(data: &[u8]) -> Vec<u8> {
+        TEST_LZS
+            .compress(SliceReader::new(data), VecWriter::with_capacity(data.len()))
+            .void_unwrap()
+    }
+
+    /// Pseudo-random bytes, which are not a stream.
+    fn noise(len: usize, mut state: u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scan() {
+        let first = include_bytes!("checkpoint.rs");
+        let second = include_bytes!("length_prefixed.rs");
+        let mut file = noise(3000, 1);
+        let offsets = [file.len(), 0, 0];
+        let stream = compress(first);
+        file.extend_from_slice(&(stream.len() as u32).to_le_bytes());
+        file.extend_from_slice(&stream);
+        file.extend_from_slice(&[0; 100]);
+
+        let offsets = [offsets[0], file.len(), 0];
+        file.extend_from_slice(&(second.len() as u32).to_le_bytes());
+        file.extend_from_slice(&compress(second));
+        file.extend_from_slice(&noise(3000, 2));
+
+        let offsets = [offsets[0], offsets[1], file.len()];
+        file.extend_from_slice(&compress(second));
+        file.extend_from_slice(&[0xFF; 3000]);
+
+        let hits = Scanner::new().scan(&file);
+        assert_eq!(
+            hits.iter().map(|hit| hit.offset).collect::<Vec<_>>(),
+            offsets
+        );
+        assert_eq!(hits[0].detection.dialect.header(), LengthHeader::Compressed);
+        assert_eq!(hits[0].decompress(&file).unwrap(), first);
+        assert_eq!(
+            hits[1].detection.dialect.header(),
+            LengthHeader::Decompressed
+        );
+        assert_eq!(hits[1].decompress(&file).unwrap(), second);
+        assert_eq!(hits[2].detection.dialect.header(), LengthHeader::None);
+        assert!(hits[2].decompress(&file).unwrap().starts_with(second));
+        assert!(hits.iter().all(|hit| hit.detection.score >= 80));
+    }
+
+    #[test]
+    fn test_scan_noise() {
+        assert_eq!(Scanner::new().scan(&noise(100_000, 3)), []);
+        assert_eq!(Scanner::new().scan(&[0; 10_000]), []);
+        assert_eq!(Scanner::new().scan(&[]), []);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::dynamic::Lzs;
+use crate::read_write::Write;
+
+/// Statistics of a compressed stream, collected from its bytes.
+///
+/// The counters have a fixed size, nothing is allocated. The bytes are either passed to
+/// [`push`](Stats::push), or collected while compressing with [`StatsWriter`]. Incomplete
+/// units at the end are not counted, like they are not decoded.
+///
+/// ```rust
+/// # use lzs::{Lzs, ResultLzsErrorVoidExt, SliceReader, Stats, StatsWriter, VecWriter};
+/// let (output, stats) = Lzs::new(0x20)
+///     .compress(
+///         SliceReader::new(b"Example Data, Example Data"),
+///         StatsWriter::new(VecWriter::with_capacity(30)),
+///     )
+///     .void_unwrap();
+/// assert_eq!(stats, Stats::of(&output));
+/// assert_eq!(stats.compressed, output.len());
+/// assert_eq!(stats.decompressed, 26);
+/// assert_eq!((stats.literals, stats.matches), (13, 1));
+/// assert_eq!(stats.lengths[13 - 3], 1); // " Example Data", with an initial space ...
+/// assert_eq!(stats.distances[3], 1); // ... 14 bytes before
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct Stats {
+    /// The number of compressed bytes.
+    pub compressed: usize,
+    /// The number of decompressed bytes.
+    pub decompressed: usize,
+    /// The number of literals.
+    pub literals: usize,
+    /// The number of matches (position-and-length pairs).
+    pub matches: usize,
+    /// The number of flag bytes, each one followed by up to eight units.
+    pub flag_groups: usize,
+    /// The number of matches which copy at least one byte of the initial fill.
+    pub prefill_matches: usize,
+    /// The number of matches by length, see [`LENGTH_BUCKETS`](Stats::LENGTH_BUCKETS).
+    pub lengths: [usize; Stats::LENGTH_BUCKETS],
+    /// The number of matches by distance, see [`DISTANCE_BUCKETS`](Stats::DISTANCE_BUCKETS).
+    pub distances: [usize; Stats::DISTANCE_BUCKETS],
+    /// The remaining flags, the high byte is used to count eight.
+    flags: usize,
+    /// The first byte of a position-and-length pair.
+    pending: Option<u8>,
+}
+
+impl Stats {
+    /// The number of buckets of [`lengths`](Stats::lengths), the length `l` is counted in the
+    /// bucket `l - THRESHOLD - 1`.
+    pub const LENGTH_BUCKETS: usize = 16;
+    /// The number of buckets of [`distances`](Stats::distances), the distance `d` is counted in
+    /// the bucket `floor(log2(d))`.
+    pub const DISTANCE_BUCKETS: usize = 13;
+
+    /// Create empty statistics.
+    #[must_use]
+    pub const fn new() -> Self {
+        Stats {
+            compressed: 0,
+            decompressed: 0,
+            literals: 0,
+            matches: 0,
+            flag_groups: 0,
+            prefill_matches: 0,
+            lengths: [0; Self::LENGTH_BUCKETS],
+            distances: [0; Self::DISTANCE_BUCKETS],
+            flags: 0,
+            pending: None,
+        }
+    }
+
+    /// Collect the statistics of a compressed stream.
+    #[must_use]
+    pub fn of(input: &[u8]) -> Self {
+        let mut stats = Stats::new();
+        for c in input {
+            stats.push(*c);
+        }
+        stats
+    }
+
+    /// Add the next byte of the compressed stream.
+    pub fn push(&mut self, c: u8) {
+        self.compressed += 1;
+        if (self.flags & 256) == 0 {
+            self.flags = c as usize | 0xFF00;
+            self.flag_groups += 1;
+        } else if (self.flags & 1) != 0 {
+            self.literals += 1;
+            self.decompressed += 1;
+            self.flags >>= 1;
+        } else if let Some(c1) = self.pending.take() {
+            let pos = c1 as usize | ((c as usize & 0xF0) << 4);
+            let len = (c as usize & 0x0F) + Lzs::threshold() + 1;
+            // The position refers to the buffer of the decoder, which starts at N - F.
+            let ring = (Lzs::n() - Lzs::f() + self.decompressed) & (Lzs::n() - 1);
+            let distance = match (ring + Lzs::n() - pos) & (Lzs::n() - 1) {
+                0 => Lzs::n(),
+                distance => distance,
+            };
+            self.matches += 1;
+            self.lengths[len - Lzs::threshold() - 1] += 1;
+            self.distances[distance.ilog2() as usize] += 1;
+            if distance > self.decompressed {
+                self.prefill_matches += 1;
+            }
+            self.decompressed += len;
+            self.flags >>= 1;
+        } else {
+            self.pending = Some(c);
+        }
+    }
+}
+
+/// Collects the [`Stats`] of the compressed bytes written to the inner writer.
+///
+/// The output is the one of the inner writer, together with the statistics.
+pub struct StatsWriter<W> {
+    writer: W,
+    stats: Stats,
+}
+
+impl<W> StatsWriter<W> {
+    /// Create a writer collecting the statistics.
+    #[inline]
+    #[must_use]
+    pub const fn new(writer: W) -> Self {
+        StatsWriter {
+            writer,
+            stats: Stats::new(),
+        }
+    }
+
+    /// The statistics collected so far.
+    #[inline]
+    #[must_use]
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+}
+
+impl<W: Write> Write for StatsWriter<W> {
+    type Output = (W::Output, Stats);
+    type Error = W::Error;
+    #[inline(always)]
+    fn write(&mut self, data: u8) -> Result<(), Self::Error> {
+        self.writer.write(data)?;
+        self.stats.push(data);
+        Ok(())
+    }
+    fn finish(self) -> Result<Self::Output, Self::Error> {
+        Ok((self.writer.finish()?, self.stats))
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use crate::dynamic::Lzs;
+    use crate::slice::SliceReader;
+    use crate::stats::{Stats, StatsWriter};
+    use crate::vec::VecWriter;
+    use crate::void::ResultLzsErrorVoidExt;
+
+    const TEST_LZS: Lzs = Lzs::new(0x20);
+
+    #[test]
+    fn test_stats() {
+        let big_test_data = include_bytes!("dynamic/mod.rs");
+        let (output, stats) = TEST_LZS
+            .compress(
+                SliceReader::new(big_test_data),
+                StatsWriter::new(VecWriter::with_capacity(big_test_data.len())),
+            )
+            .void_unwrap();
+        assert_eq!(stats, Stats::of(&output));
+        assert_eq!(stats.compressed, output.len());
+        assert_eq!(stats.decompressed, big_test_data.len());
+        assert_eq!(
+            stats.flag_groups,
+            (stats.literals + stats.matches).div_ceil(8)
+        );
+        assert_eq!(stats.lengths.iter().sum::<usize>(), stats.matches);
+        assert_eq!(stats.distances.iter().sum::<usize>(), stats.matches);
+        assert_eq!(
+            stats.compressed,
+            stats.flag_groups + stats.literals + 2 * stats.matches
+        );
+        // the indentation is found in the initial spaces
+        assert!(stats.prefill_matches > 0);
+
+        // incomplete units are not counted
+        let truncated = Stats::of(&output[..output.len() - 1]);
+        assert_eq!(truncated.compressed, output.len() - 1);
+        assert!(truncated.decompressed < big_test_data.len());
+    }
+
+    #[test]
+    fn test_stats_prefill() {
+        let output = TEST_LZS
+            .compress(SliceReader::new(&[0x20; 100]), VecWriter::with_capacity(30))
+            .void_unwrap();
+        let stats = Stats::of(&output);
+        assert_eq!(stats.literals, 0);
+        // the first match is found in the initial spaces, the following ones nearer
+        assert_eq!(stats.prefill_matches, 1);
+        assert_eq!(stats.lengths[15], 5);
+        assert_eq!(stats.lengths[10 - 3], 1);
+        assert_eq!(stats.decompressed, 100);
+    }
+}
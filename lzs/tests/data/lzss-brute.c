@@ -0,0 +1,71 @@
+/* The encoding loop of lzss.c by Haruhiko Okumura (brute-force search from the nearest
+   position, keeping the first longest match), with EI = 12, EJ = 4, P = 2 and the
+   units packed like LZSS.C: a flag byte before eight of them, 1 for a literal. */
+#include <stdio.h>
+#include <stdlib.h>
+#define EI 12
+#define EJ 4
+#define P 2
+#define N (1 << EI)
+#define F ((1 << EJ) + P)
+
+unsigned char buffer[N * 2];
+unsigned char code_buf[17];
+int code_buf_ptr = 1, mask = 1;
+FILE *infile, *outfile;
+
+void unit_done(void) {
+    if ((mask <<= 1) == 0x100) {
+        fwrite(code_buf, 1, code_buf_ptr, outfile);
+        code_buf[0] = 0; code_buf_ptr = 1; mask = 1;
+    }
+}
+void output1(int c) { code_buf[0] |= mask; code_buf[code_buf_ptr++] = c; unit_done(); }
+void output2(int x, int y) {
+    code_buf[code_buf_ptr++] = x & 0xff;
+    code_buf[code_buf_ptr++] = ((x >> 4) & 0xf0) | (y - (P + 1));
+    unit_done();
+}
+
+void encode(void) {
+    int i, j, f1, x, y, r, s, bufferend, c;
+    for (i = 0; i < N - F; i++) buffer[i] = ' ';
+    for (i = N - F; i < N * 2; i++) {
+        if ((c = getc(infile)) == EOF) break;
+        buffer[i] = c;
+    }
+    bufferend = i;  r = N - F;  s = 0;
+    while (r < bufferend) {
+        f1 = (F <= bufferend - r) ? F : bufferend - r;
+        x = 0;  y = 1;  c = buffer[r];
+        for (i = r - 1; i >= s; i--)
+            if (buffer[i] == c) {
+                for (j = 1; j < f1; j++)
+                    if (buffer[i + j] != buffer[r + j]) break;
+                if (j > y) {
+                    x = i;  y = j;
+                }
+            }
+        if (y <= P) {  y = 1;  output1(c);  }
+        else output2(x & (N - 1), y);
+        r += y;  s += y;
+        if (r >= N * 2 - F) {
+            for (i = 0; i < N; i++) buffer[i] = buffer[i + N];
+            bufferend -= N;  r -= N;  s -= N;
+            while (bufferend < N * 2) {
+                if ((c = getc(infile)) == EOF) break;
+                buffer[bufferend++] = c;
+            }
+        }
+    }
+    if (code_buf_ptr > 1) fwrite(code_buf, 1, code_buf_ptr, outfile);
+}
+
+int main(int argc, char *argv[]) {
+    if (argc != 3) return EXIT_FAILURE;
+    infile = fopen(argv[1], "rb"); outfile = fopen(argv[2], "wb");
+    if (!infile || !outfile) return EXIT_FAILURE;
+    encode();
+    fclose(infile); fclose(outfile);
+    return EXIT_SUCCESS;
+}